@@ -1,4 +1,6 @@
 use divan::{black_box, Bencher};
+use lance_rle_benchmark::projection::ColumnSelection;
+use lance_rle_benchmark::size::batch_size;
 use lance_rle_benchmark::{data::*, lance, parquet};
 use tokio::runtime::Runtime;
 
@@ -19,11 +21,13 @@ macro_rules! bench_write {
                 let batch = generate_record_batch(N);
                 bencher
                     .counter(divan::counter::BytesCount::new(
-                        N * (8 + 8 * 3827), // uuid + 3827 double features
+                        batch_size(&batch).unwrap().logical,
                     ))
                     .bench_local(|| {
                         rt.block_on(async {
-                            let bytes = lance::write_bytes(batch.clone(), false, false).await;
+                            let bytes = lance::write_bytes(batch.clone(), false, false)
+                                .await
+                                .unwrap();
                             black_box(bytes)
                         })
                     });
@@ -35,11 +39,13 @@ macro_rules! bench_write {
                 let batch = generate_record_batch(N);
                 bencher
                     .counter(divan::counter::BytesCount::new(
-                        N * (8 + 8 * 3827), // uuid + 3827 double features
+                        batch_size(&batch).unwrap().logical,
                     ))
                     .bench_local(|| {
                         rt.block_on(async {
-                            let bytes = lance::write_bytes(batch.clone(), false, true).await;
+                            let bytes = lance::write_bytes(batch.clone(), false, true)
+                                .await
+                                .unwrap();
                             black_box(bytes)
                         })
                     });
@@ -50,10 +56,10 @@ macro_rules! bench_write {
                 let batch = generate_record_batch(N);
                 bencher
                     .counter(divan::counter::BytesCount::new(
-                        N * (8 + 8 * 3827), // uuid + 3827 double features
+                        batch_size(&batch).unwrap().logical,
                     ))
                     .bench_local(|| {
-                        let bytes = parquet::write_bytes(batch.clone());
+                        let bytes = parquet::write_bytes(batch.clone()).unwrap();
                         black_box(bytes)
                     });
             }
@@ -72,14 +78,16 @@ macro_rules! bench_read {
             fn lance_bitpacking(bencher: Bencher) {
                 let rt = Runtime::new().unwrap();
                 let batch = generate_record_batch(N);
-                let bytes = rt.block_on(lance::write_bytes(batch.clone(), false, false));
+                let bytes = rt
+                    .block_on(lance::write_bytes(batch.clone(), false, false))
+                    .unwrap();
                 bencher
                     .counter(divan::counter::BytesCount::new(
-                        N * (8 + 8 * 3827), // uuid + 3827 double features
+                        batch_size(&batch).unwrap().logical,
                     ))
                     .bench_local(|| {
                         rt.block_on(async {
-                            let batches = lance::read_bytes(&bytes).await;
+                            let batches = lance::read_bytes(&bytes).await.unwrap();
                             black_box(batches)
                         })
                     });
@@ -89,14 +97,16 @@ macro_rules! bench_read {
             fn lance_rle(bencher: Bencher) {
                 let rt = Runtime::new().unwrap();
                 let batch = generate_record_batch(N);
-                let bytes = rt.block_on(lance::write_bytes(batch.clone(), false, true));
+                let bytes = rt
+                    .block_on(lance::write_bytes(batch.clone(), false, true))
+                    .unwrap();
                 bencher
                     .counter(divan::counter::BytesCount::new(
-                        N * (8 + 8 * 3827), // uuid + 3827 double features
+                        batch_size(&batch).unwrap().logical,
                     ))
                     .bench_local(|| {
                         rt.block_on(async {
-                            let batches = lance::read_bytes(&bytes).await;
+                            let batches = lance::read_bytes(&bytes).await.unwrap();
                             black_box(batches)
                         })
                     });
@@ -105,13 +115,13 @@ macro_rules! bench_read {
             #[divan::bench]
             fn parquet(bencher: Bencher) {
                 let batch = generate_record_batch(N);
-                let bytes = parquet::write_bytes(batch.clone());
+                let bytes = parquet::write_bytes(batch.clone()).unwrap();
                 bencher
                     .counter(divan::counter::BytesCount::new(
-                        N * (8 + 8 * 3827), // uuid + 3827 double features
+                        batch_size(&batch).unwrap().logical,
                     ))
                     .bench_local(|| {
-                        let batches = parquet::read_bytes(&bytes);
+                        let batches = parquet::read_bytes(&bytes).unwrap();
                         black_box(batches)
                     });
             }
@@ -130,11 +140,13 @@ macro_rules! bench_take {
             fn lance_bitpacking(bencher: Bencher) {
                 let rt = Runtime::new().unwrap();
                 let batch = generate_record_batch(N);
-                let bytes = rt.block_on(lance::write_bytes(batch, false, false));
+                let bytes = rt
+                    .block_on(lance::write_bytes(batch, false, false))
+                    .unwrap();
                 let indices = vec![N / 2]; // Take single row from middle
                 bencher.counter(indices.len() as u64).bench_local(|| {
                     rt.block_on(async {
-                        let result = lance::take_rows_from_bytes(&bytes, &indices).await;
+                        let result = lance::take_rows_from_bytes(&bytes, &indices).await.unwrap();
                         black_box(result)
                     })
                 });
@@ -144,11 +156,11 @@ macro_rules! bench_take {
             fn lance_rle(bencher: Bencher) {
                 let rt = Runtime::new().unwrap();
                 let batch = generate_record_batch(N);
-                let bytes = rt.block_on(lance::write_bytes(batch, false, true));
+                let bytes = rt.block_on(lance::write_bytes(batch, false, true)).unwrap();
                 let indices = vec![N / 2]; // Take single row from middle
                 bencher.counter(indices.len() as u64).bench_local(|| {
                     rt.block_on(async {
-                        let result = lance::take_rows_from_bytes(&bytes, &indices).await;
+                        let result = lance::take_rows_from_bytes(&bytes, &indices).await.unwrap();
                         black_box(result)
                     })
                 });
@@ -157,10 +169,113 @@ macro_rules! bench_take {
             #[divan::bench]
             fn parquet(bencher: Bencher) {
                 let batch = generate_record_batch(N);
-                let bytes = parquet::write_bytes(batch);
+                let bytes = parquet::write_bytes(batch).unwrap();
                 let indices = vec![N / 2]; // Take single row from middle
                 bencher.counter(indices.len() as u64).bench_local(|| {
-                    let result = parquet::take_rows_from_bytes(&bytes, &indices);
+                    let result = parquet::take_rows_from_bytes(&bytes, &indices).unwrap();
+                    black_box(result)
+                });
+            }
+        }
+    };
+}
+
+macro_rules! bench_projected {
+    ($mod_name:ident, $group_name:expr, $size:expr) => {
+        #[divan::bench_group(name = $group_name)]
+        mod $mod_name {
+            use super::*;
+            const N: usize = $size;
+            const WIDTHS: [usize; 4] = [1, 10, 50, 500];
+
+            fn columns(width: usize) -> Vec<String> {
+                let batch = generate_record_batch(1);
+                ColumnSelection::FirstK(width).column_paths(&batch.schema())
+            }
+
+            #[divan::bench(args = WIDTHS)]
+            fn scan_lance_bitpacking(bencher: Bencher, width: usize) {
+                let rt = Runtime::new().unwrap();
+                let bytes = rt
+                    .block_on(lance::write_bytes(generate_record_batch(N), false, false))
+                    .unwrap();
+                let columns = columns(width);
+                bencher.bench_local(|| {
+                    rt.block_on(async {
+                        let result = lance::read_bytes_projected(&bytes, &columns).await.unwrap();
+                        black_box(result)
+                    })
+                });
+            }
+
+            #[divan::bench(args = WIDTHS)]
+            fn scan_lance_rle(bencher: Bencher, width: usize) {
+                let rt = Runtime::new().unwrap();
+                let bytes = rt
+                    .block_on(lance::write_bytes(generate_record_batch(N), false, true))
+                    .unwrap();
+                let columns = columns(width);
+                bencher.bench_local(|| {
+                    rt.block_on(async {
+                        let result = lance::read_bytes_projected(&bytes, &columns).await.unwrap();
+                        black_box(result)
+                    })
+                });
+            }
+
+            #[divan::bench(args = WIDTHS)]
+            fn scan_parquet(bencher: Bencher, width: usize) {
+                let bytes = parquet::write_bytes(generate_record_batch(N)).unwrap();
+                let columns = columns(width);
+                bencher.bench_local(|| {
+                    let result = parquet::read_bytes_projected(&bytes, &columns).unwrap();
+                    black_box(result)
+                });
+            }
+
+            #[divan::bench(args = WIDTHS)]
+            fn take_lance_bitpacking(bencher: Bencher, width: usize) {
+                let rt = Runtime::new().unwrap();
+                let bytes = rt
+                    .block_on(lance::write_bytes(generate_record_batch(N), false, false))
+                    .unwrap();
+                let columns = columns(width);
+                let indices = vec![N / 2];
+                bencher.bench_local(|| {
+                    rt.block_on(async {
+                        let result = lance::take_rows_projected(&bytes, &indices, &columns)
+                            .await
+                            .unwrap();
+                        black_box(result)
+                    })
+                });
+            }
+
+            #[divan::bench(args = WIDTHS)]
+            fn take_lance_rle(bencher: Bencher, width: usize) {
+                let rt = Runtime::new().unwrap();
+                let bytes = rt
+                    .block_on(lance::write_bytes(generate_record_batch(N), false, true))
+                    .unwrap();
+                let columns = columns(width);
+                let indices = vec![N / 2];
+                bencher.bench_local(|| {
+                    rt.block_on(async {
+                        let result = lance::take_rows_projected(&bytes, &indices, &columns)
+                            .await
+                            .unwrap();
+                        black_box(result)
+                    })
+                });
+            }
+
+            #[divan::bench(args = WIDTHS)]
+            fn take_parquet(bencher: Bencher, width: usize) {
+                let bytes = parquet::write_bytes(generate_record_batch(N)).unwrap();
+                let columns = columns(width);
+                let indices = vec![N / 2];
+                bencher.bench_local(|| {
+                    let result = parquet::take_rows_projected(&bytes, &indices, &columns).unwrap();
                     black_box(result)
                 });
             }
@@ -181,4 +296,7 @@ bench_read!(read_100k, "read/100k_rows", 100_000);
 // Take benchmarks with realistic data
 bench_take!(take_1k, "take/1k_rows/single", 1_000);
 bench_take!(take_10k, "take/10k_rows/single", 10_000);
-bench_take!(take_100k, "take/100k_rows/single", 100_000);
\ No newline at end of file
+bench_take!(take_100k, "take/100k_rows/single", 100_000);
+// Projected scan/take benchmarks over the first k features
+bench_projected!(projected_10k, "projected/10k_rows", 10_000);
+bench_projected!(projected_100k, "projected/100k_rows", 100_000);
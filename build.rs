@@ -0,0 +1,68 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+// Bake the toolchain and the locked dependency versions into the binary, so
+// reports can say exactly what produced them.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BENCH_RUSTC_VERSION={}", rustc_version);
+    println!(
+        "cargo:rustc-env=BENCH_PROFILE={}",
+        env::var("PROFILE").unwrap_or_default()
+    );
+    println!(
+        "cargo:rustc-env=BENCH_OPT_LEVEL={}",
+        env::var("OPT_LEVEL").unwrap_or_default()
+    );
+
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    for (name, var) in [
+        ("lance", "LANCE"),
+        ("parquet", "PARQUET"),
+        ("arrow", "ARROW"),
+    ] {
+        let (version, source) = locked_package(&lock, name);
+        println!("cargo:rustc-env=BENCH_{}_VERSION={}", var, version);
+        // Git sources end in `#<full commit>`
+        let rev = source.rsplit_once('#').map_or("", |(_, rev)| rev);
+        println!("cargo:rustc-env=BENCH_{}_REV={}", var, rev);
+    }
+}
+
+// Version and source of the `[[package]]` entry called `name`
+fn locked_package(lock: &str, name: &str) -> (String, String) {
+    let mut version = String::new();
+    let mut source = String::new();
+    let mut found = false;
+    for line in lock.lines() {
+        if line == "[[package]]" {
+            if found {
+                break;
+            }
+            version.clear();
+            source.clear();
+        } else if let Some(value) = line.strip_prefix("name = ") {
+            found = value.trim_matches('"') == name;
+        } else if let Some(value) = line.strip_prefix("version = ") {
+            version = value.trim_matches('"').to_string();
+        } else if let Some(value) = line.strip_prefix("source = ") {
+            source = value.trim_matches('"').to_string();
+        }
+    }
+    if found {
+        (version, source)
+    } else {
+        ("unknown".to_string(), String::new())
+    }
+}
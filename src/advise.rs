@@ -0,0 +1,287 @@
+use crate::format::LanceCompression;
+use arrow_schema::{DataType, Field, Fields, Schema};
+use lance_core::datatypes::COMPRESSION_META_KEY;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+// One compression tried on one column, written on its own
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    pub compression: LanceCompression,
+    pub bytes: u64,
+    // Median time to scan the single-column file
+    pub scan_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnCandidates {
+    pub column: String,
+    pub candidates: Vec<Candidate>,
+}
+
+// What to minimize, and the budget the other measure must stay within.
+// Budgets are totals over all columns; scan time adds up because a full scan
+// decodes every column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    MinSize { max_scan_ms: Option<f64> },
+    MinLatency { max_bytes: Option<u64> },
+}
+
+impl Objective {
+    // (minimized, budgeted) cost of a candidate
+    fn costs(&self, candidate: &Candidate) -> (f64, f64) {
+        match self {
+            Objective::MinSize { .. } => (candidate.bytes as f64, candidate.scan_ms),
+            Objective::MinLatency { .. } => (candidate.scan_ms, candidate.bytes as f64),
+        }
+    }
+
+    fn budget(&self) -> Option<f64> {
+        match self {
+            Objective::MinSize { max_scan_ms } => *max_scan_ms,
+            Objective::MinLatency { max_bytes } => max_bytes.map(|bytes| bytes as f64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub column: String,
+    pub candidate: Candidate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advice {
+    pub choices: Vec<Choice>,
+    pub total_bytes: u64,
+    pub total_scan_ms: f64,
+    // False if no combination of candidates meets the budget; the advice is
+    // then the closest the search got
+    pub within_budget: bool,
+}
+
+// Start from each column's cheapest candidate, then while over budget make
+// the switch that buys the most budget per unit of added cost. Every column
+// needs at least one candidate.
+pub fn choose(columns: &[ColumnCandidates], objective: Objective) -> Advice {
+    let mut picks: Vec<usize> = columns
+        .iter()
+        .map(|column| {
+            (0..column.candidates.len())
+                .min_by(|&a, &b| {
+                    let a = objective.costs(&column.candidates[a]);
+                    let b = objective.costs(&column.candidates[b]);
+                    a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+                })
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let spent = |picks: &[usize]| -> f64 {
+        columns
+            .iter()
+            .zip(picks)
+            .map(|(column, &pick)| objective.costs(&column.candidates[pick]).1)
+            .sum()
+    };
+
+    let mut within_budget = true;
+    if let Some(budget) = objective.budget() {
+        while spent(&picks) > budget {
+            let mut best: Option<(f64, usize, usize)> = None;
+            for (i, column) in columns.iter().enumerate() {
+                let (cost, used) = objective.costs(&column.candidates[picks[i]]);
+                for (j, candidate) in column.candidates.iter().enumerate() {
+                    let (new_cost, new_used) = objective.costs(candidate);
+                    let saved = used - new_used;
+                    if saved <= 0.0 {
+                        continue;
+                    }
+                    let price = (new_cost - cost).max(0.0) / saved;
+                    if best.map_or(true, |(lowest, _, _)| price < lowest) {
+                        best = Some((price, i, j));
+                    }
+                }
+            }
+            match best {
+                Some((_, i, j)) => picks[i] = j,
+                None => {
+                    within_budget = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    let choices: Vec<Choice> = columns
+        .iter()
+        .zip(&picks)
+        .map(|(column, &pick)| Choice {
+            column: column.column.clone(),
+            candidate: column.candidates[pick],
+        })
+        .collect();
+    Advice {
+        total_bytes: choices.iter().map(|c| c.candidate.bytes).sum(),
+        total_scan_ms: choices.iter().map(|c| c.candidate.scan_ms).sum(),
+        choices,
+        within_budget,
+    }
+}
+
+impl Advice {
+    // Field metadata per dotted column path
+    pub fn metadata(&self) -> BTreeMap<String, HashMap<String, String>> {
+        self.choices
+            .iter()
+            .map(|choice| {
+                let metadata = HashMap::from([(
+                    COMPRESSION_META_KEY.to_string(),
+                    choice.candidate.compression.as_str().to_string(),
+                )]);
+                (choice.column.clone(), metadata)
+            })
+            .collect()
+    }
+
+    // `schema` with the advised compression set on every leaf it covers,
+    // ready for `lance::write_annotated_bytes`
+    pub fn apply(&self, schema: &Schema) -> Schema {
+        let metadata = self.metadata();
+        let fields: Vec<Field> = schema
+            .fields()
+            .iter()
+            .map(|field| annotate(field, field.name(), &metadata))
+            .collect();
+        Schema::new_with_metadata(fields, schema.metadata().clone())
+    }
+}
+
+fn annotate(
+    field: &Field,
+    path: &str,
+    metadata: &BTreeMap<String, HashMap<String, String>>,
+) -> Field {
+    let field = match field.data_type() {
+        DataType::Struct(children) => {
+            let children: Fields = children
+                .iter()
+                .map(|child| {
+                    let path = format!("{}.{}", path, child.name());
+                    Arc::new(annotate(child, &path, metadata))
+                })
+                .collect();
+            field.clone().with_data_type(DataType::Struct(children))
+        }
+        _ => field.clone(),
+    };
+    match metadata.get(path) {
+        Some(extra) => {
+            let mut merged = field.metadata().clone();
+            merged.extend(extra.clone());
+            field.with_metadata(merged)
+        }
+        None => field,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(compression: LanceCompression, bytes: u64, scan_ms: f64) -> Candidate {
+        Candidate {
+            compression,
+            bytes,
+            scan_ms,
+        }
+    }
+
+    // Rle is smaller and slower to scan than bitpacking on both columns, by
+    // more on `b`
+    fn columns() -> Vec<ColumnCandidates> {
+        vec![
+            ColumnCandidates {
+                column: "a".to_string(),
+                candidates: vec![
+                    candidate(LanceCompression::Bitpacking, 100, 1.0),
+                    candidate(LanceCompression::Rle, 90, 2.0),
+                ],
+            },
+            ColumnCandidates {
+                column: "b".to_string(),
+                candidates: vec![
+                    candidate(LanceCompression::Bitpacking, 100, 1.0),
+                    candidate(LanceCompression::Rle, 50, 2.0),
+                ],
+            },
+        ]
+    }
+
+    fn picks(advice: &Advice) -> Vec<LanceCompression> {
+        advice
+            .choices
+            .iter()
+            .map(|c| c.candidate.compression)
+            .collect()
+    }
+
+    #[test]
+    fn cheapest_candidates_within_budget() {
+        let advice = choose(
+            &columns(),
+            Objective::MinSize {
+                max_scan_ms: Some(4.0),
+            },
+        );
+        assert!(advice.within_budget);
+        assert_eq!(picks(&advice), [LanceCompression::Rle; 2]);
+        assert_eq!(advice.total_bytes, 140);
+        assert_eq!(advice.total_scan_ms, 4.0);
+    }
+
+    #[test]
+    fn over_budget_gives_up_the_smallest_saving() {
+        let advice = choose(
+            &columns(),
+            Objective::MinSize {
+                max_scan_ms: Some(3.0),
+            },
+        );
+        assert!(advice.within_budget);
+        // Going back to bitpacking costs 10 bytes on `a` and 50 on `b`
+        assert_eq!(
+            picks(&advice),
+            [LanceCompression::Bitpacking, LanceCompression::Rle]
+        );
+        assert_eq!(advice.total_bytes, 150);
+    }
+
+    #[test]
+    fn impossible_budget_is_flagged() {
+        let advice = choose(
+            &columns(),
+            Objective::MinSize {
+                max_scan_ms: Some(1.0),
+            },
+        );
+        assert!(!advice.within_budget);
+        assert_eq!(advice.total_scan_ms, 2.0);
+    }
+
+    #[test]
+    fn latency_with_a_size_budget() {
+        let advice = choose(
+            &columns(),
+            Objective::MinLatency {
+                max_bytes: Some(160),
+            },
+        );
+        assert!(advice.within_budget);
+        assert_eq!(
+            picks(&advice),
+            [LanceCompression::Bitpacking, LanceCompression::Rle]
+        );
+    }
+}
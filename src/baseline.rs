@@ -0,0 +1,343 @@
+use crate::error::Result;
+use crate::provenance::Provenance;
+use crate::results::{read_records, write_records, Record, ResultFormat};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::Hash;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// Baselines are plain results files, stored as Parquet under a directory so
+// they can be committed or cached next to the lance rev they were taken at.
+pub fn baseline_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.parquet", name))
+}
+
+pub fn save(
+    records: &[Record],
+    provenance: &Provenance,
+    dir: &Path,
+    name: &str,
+) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = baseline_path(dir, name);
+    write_records(&provenance.stamp(records), &path, ResultFormat::Parquet)?;
+    provenance.write_sidecar(&path)?;
+    Ok(path)
+}
+
+// Save an existing results file as the named baseline, its provenance
+// sidecar along with it if there is one
+pub fn save_file(results: &Path, format: ResultFormat, dir: &Path, name: &str) -> Result<PathBuf> {
+    let records = read_records(results, format)?;
+    fs::create_dir_all(dir)?;
+    let path = baseline_path(dir, name);
+    write_records(&records, &path, ResultFormat::Parquet)?;
+    let sidecar = Provenance::sidecar_path(results);
+    if sidecar.exists() {
+        fs::copy(&sidecar, Provenance::sidecar_path(&path))?;
+    }
+    Ok(path)
+}
+
+pub fn load(dir: &Path, name: &str) -> Result<Vec<Record>> {
+    read_records(&baseline_path(dir, name), ResultFormat::Parquet)
+}
+
+// Change of one metric of one measurement between a baseline and a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delta {
+    pub step: String,
+    pub config: String,
+    pub schema: String,
+    pub rows: u64,
+    pub columns: Option<String>,
+    pub metric: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+    pub change_pct: f64,
+    pub regression: bool,
+    // Set when the measurement worked in the baseline and fails now
+    pub error: Option<String>,
+}
+
+// What a measurement was taken of, apart from the step
+fn subject(r: &Record) -> impl Hash + Eq {
+    (
+        r.config.clone(),
+        r.schema.clone(),
+        r.rows,
+        r.columns.clone(),
+        r.ordering.clone(),
+        r.layout.clone(),
+        r.pieces,
+        r.stage.clone(),
+        r.section.clone(),
+        r.cell.clone(),
+        r.page_size,
+        r.distribution.clone(),
+    )
+}
+
+// Steps that write a table and so own its size; the reads after them repeat
+// it
+const WRITE_STEPS: [&str; 8] = [
+    "compress",
+    "write",
+    "split-write",
+    "dataset-write",
+    "mutate",
+    "compaction-append",
+    "compact",
+    "add-columns",
+];
+
+// A measurement that worked in the baseline and has no result now
+fn status(record: &Record, error: &str) -> Delta {
+    Delta {
+        step: record.step.clone(),
+        config: record.config.clone(),
+        schema: record.schema.clone(),
+        rows: record.rows,
+        columns: record.columns.clone(),
+        metric: "status",
+        baseline: 0.0,
+        current: 0.0,
+        change_pct: 0.0,
+        regression: true,
+        error: Some(error.to_string()),
+    }
+}
+
+// Both metrics are "lower is better". A latency only counts as a regression
+// when it is past the threshold and its confidence interval no longer
+// overlaps the baseline's, so noisy runs do not fail CI. Anything that
+// worked in the baseline and fails now, or is no longer measured at all, is
+// always a regression.
+pub fn compare(baseline: &[Record], current: &[Record], threshold_pct: f64) -> Vec<Delta> {
+    let key = |r: &Record| (r.step.clone(), subject(r));
+    let baseline_ok: Vec<&Record> = baseline.iter().filter(|r| r.error.is_none()).collect();
+    // A failed step can be named differently from the ones that worked,
+    // e.g. a `write` error where the baseline has `compress`, so failures
+    // are matched on the subject alone
+    let worked: HashSet<_> = baseline_ok.iter().map(|r| subject(r)).collect();
+    let baseline: HashMap<_, &Record> = baseline_ok.iter().map(|r| (key(r), *r)).collect();
+
+    let mut deltas = Vec::new();
+    for record in current {
+        if let Some(error) = &record.error {
+            if worked.contains(&subject(record)) {
+                deltas.push(status(record, error));
+            }
+            continue;
+        }
+        let Some(old) = baseline.get(&key(record)) else {
+            continue;
+        };
+        let delta = |metric, before: f64, after: f64| {
+            let change_pct = if before == 0.0 {
+                0.0
+            } else {
+                (after - before) / before * 100.0
+            };
+            Delta {
+                step: record.step.clone(),
+                config: record.config.clone(),
+                schema: record.schema.clone(),
+                rows: record.rows,
+                columns: record.columns.clone(),
+                metric,
+                baseline: before,
+                current: after,
+                change_pct,
+                regression: change_pct > threshold_pct,
+                error: None,
+            }
+        };
+
+        if WRITE_STEPS.contains(&record.step.as_str()) {
+            if let (Some(old), Some(new)) = (old.file_bytes, record.file_bytes) {
+                deltas.push(delta("bytes", old as f64, new as f64));
+            }
+        }
+        if let (Some(old_ms), Some(new_ms)) = (old.latency_ms, record.latency_ms) {
+            let mut latency = delta("ms", old_ms, new_ms);
+            // Without intervals, e.g. from too few repetitions, the
+            // threshold alone decides
+            let old_high = old.timing.and_then(|t| t.ci_high_ms);
+            let new_low = record.timing.and_then(|t| t.ci_low_ms);
+            if let (Some(old_high), Some(new_low)) = (old_high, new_low) {
+                latency.regression &= new_low > old_high;
+            }
+            deltas.push(latency);
+        }
+    }
+
+    // Failures were reported above under whatever step they were named
+    let measured: HashSet<_> = current
+        .iter()
+        .filter(|r| r.error.is_none())
+        .map(key)
+        .collect();
+    let failed: HashSet<_> = current
+        .iter()
+        .filter(|r| r.error.is_some())
+        .map(subject)
+        .collect();
+    for old in baseline_ok {
+        if !measured.contains(&key(old)) && !failed.contains(&subject(old)) {
+            deltas.push(status(old, "not measured in this run"));
+        }
+    }
+    deltas
+}
+
+pub fn write_deltas<W: Write>(out: &mut W, name: &str, deltas: &[Delta]) -> Result<()> {
+    writeln!(out, "\n=== Comparison against baseline `{}` ===", name)?;
+    writeln!(
+        out,
+        "\n| Step | Schema | Rows | Columns | Config | Metric | Baseline | Current | Change |"
+    )?;
+    writeln!(
+        out,
+        "|------|--------|------|---------|--------|--------|----------|---------|--------|"
+    )?;
+    for delta in deltas {
+        if let Some(error) = &delta.error {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} | ok | failed | **failed**: {} |",
+                delta.step,
+                delta.schema,
+                delta.rows,
+                delta.columns.as_deref().unwrap_or("-"),
+                delta.config,
+                delta.metric,
+                error,
+            )?;
+            continue;
+        }
+        writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} | {:.2} | {:.2} | {}{:+.1}%{} |",
+            delta.step,
+            delta.schema,
+            delta.rows,
+            delta.columns.as_deref().unwrap_or("-"),
+            delta.config,
+            delta.metric,
+            delta.baseline,
+            delta.current,
+            if delta.regression { "**" } else { "" },
+            delta.change_pct,
+            if delta.regression { "**" } else { "" },
+        )?;
+    }
+    let regressions = deltas.iter().filter(|d| d.regression).count();
+    writeln!(
+        out,
+        "\n**Note**: {} regression(s), marked with **bold**.",
+        regressions
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(step: &str, error: Option<&str>) -> Record {
+        Record {
+            step: step.to_string(),
+            config: "lance-rle".to_string(),
+            schema: "nested".to_string(),
+            rows: 1_000,
+            file_bytes: error.is_none().then_some(100),
+            error: error.map(str::to_string),
+            ..Record::default()
+        }
+    }
+
+    #[test]
+    fn newly_failing_configuration_is_a_regression() {
+        let baseline = [record("compress", None)];
+        let current = [record("write", Some("boom"))];
+        let deltas = compare(&baseline, &current, 5.0);
+        assert_eq!(deltas.len(), 1);
+        assert!(deltas[0].regression);
+        assert_eq!(deltas[0].error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn failing_in_both_is_not_a_regression() {
+        let baseline = [record("write", Some("boom"))];
+        let current = [record("write", Some("boom"))];
+        assert!(compare(&baseline, &current, 5.0).is_empty());
+    }
+
+    #[test]
+    fn sweep_cells_are_compared_with_themselves() {
+        let cell = |page_size| Record {
+            cell: Some(format!("parquet-zstd/page-{}", page_size)),
+            page_size: Some(page_size),
+            ..record("compress", None)
+        };
+        let baseline = [
+            cell(4096),
+            Record {
+                file_bytes: Some(50),
+                ..cell(65536)
+            },
+        ];
+        let current = [
+            Record {
+                file_bytes: Some(200),
+                ..cell(4096)
+            },
+            Record {
+                file_bytes: Some(50),
+                ..cell(65536)
+            },
+        ];
+        let deltas = compare(&baseline, &current, 5.0);
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].baseline, 100.0);
+        assert_eq!(deltas[1].baseline, 50.0);
+    }
+
+    #[test]
+    fn measurement_missing_from_the_run_is_a_regression() {
+        let baseline = [record("compress", None), record("scan", None)];
+        let current = [record("compress", None)];
+        let deltas = compare(&baseline, &current, 5.0);
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[1].step, "scan");
+        assert_eq!(deltas[1].metric, "status");
+        assert!(deltas[1].regression);
+    }
+
+    #[test]
+    fn write_steps_compare_sizes() {
+        let baseline = [record("mutate", None)];
+        let current = [Record {
+            file_bytes: Some(150),
+            ..record("mutate", None)
+        }];
+        let deltas = compare(&baseline, &current, 5.0);
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].metric, "bytes");
+        assert!(deltas[0].regression);
+    }
+
+    #[test]
+    fn size_growth_past_threshold_is_a_regression() {
+        let baseline = [record("compress", None)];
+        let current = [Record {
+            file_bytes: Some(110),
+            ..record("compress", None)
+        }];
+        let deltas = compare(&baseline, &current, 5.0);
+        assert_eq!(deltas.len(), 1);
+        assert!(deltas[0].regression);
+    }
+}
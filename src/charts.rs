@@ -0,0 +1,307 @@
+use crate::error::{Error, Result};
+use crate::results::Record;
+use plotters::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SIZE: (u32, u32) = (900, 540);
+const FONT: &str = "sans-serif";
+
+fn chart_error(error: impl Display) -> Error {
+    Error::Chart(error.to_string())
+}
+
+// Render every chart the records have data for into `dir`, plus an
+// `index.html` that shows them on one page. Returns the written files. A
+// results file appended to by several runs is charted for the latest one.
+pub fn render(records: &[Record], dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+
+    let session = records.last().and_then(|r| r.session.as_ref());
+    let mut by_schema: BTreeMap<&str, Vec<&Record>> = BTreeMap::new();
+    for record in records
+        .iter()
+        .filter(|r| r.error.is_none() && r.session.as_ref() == session)
+    {
+        by_schema
+            .entry(record.schema.as_str())
+            .or_default()
+            .push(record);
+    }
+
+    let mut files = Vec::new();
+    for (schema, records) in &by_schema {
+        let path = dir.join(format!("ratio-{}.svg", schema));
+        if ratio_vs_rows(&path, schema, records)? {
+            files.push(path);
+        }
+        let path = dir.join(format!("size-vs-scan-{}.svg", schema));
+        if size_vs_scan(&path, schema, records)? {
+            files.push(path);
+        }
+        let path = dir.join(format!("take-latency-{}.svg", schema));
+        if take_latency(&path, schema, records)? {
+            files.push(path);
+        }
+    }
+
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\">\
+         <title>Lance RLE benchmark</title></head>\n<body>\n",
+    );
+    for file in &files {
+        let name = file.file_name().unwrap().to_string_lossy();
+        html.push_str(&format!("<p><img src=\"{}\" alt=\"{}\"></p>\n", name, name));
+    }
+    html.push_str("</body>\n</html>\n");
+    let index = dir.join("index.html");
+    fs::write(&index, html)?;
+    files.push(index);
+    Ok(files)
+}
+
+// The configuration plus whatever else `baseline::subject` tells records
+// apart by, so sweeps and reordered or split tables get their own series
+fn series_label(r: &Record) -> String {
+    let layout = r.layout.as_ref().map(|layout| match r.pieces {
+        Some(pieces) => format!("{} {}", layout, pieces),
+        None => layout.clone(),
+    });
+    let dimensions = [
+        r.ordering.clone(),
+        layout,
+        r.stage.clone(),
+        r.page_size.map(|size| format!("page {}", size)),
+        r.distribution.clone(),
+    ];
+    let mut label = r.config.clone();
+    for dimension in dimensions.into_iter().flatten() {
+        label.push_str(&format!(" {}", dimension));
+    }
+    label
+}
+
+// One line per configuration, compression ratio against row count
+fn ratio_vs_rows(path: &Path, schema: &str, records: &[&Record]) -> Result<bool> {
+    let mut series: BTreeMap<String, BTreeMap<u64, f64>> = BTreeMap::new();
+    for record in records.iter().filter(|r| r.step == "compress") {
+        if let Some(ratio) = record.ratio {
+            series
+                .entry(series_label(record))
+                .or_default()
+                .insert(record.rows, ratio);
+        }
+    }
+    if series.is_empty() {
+        return Ok(false);
+    }
+
+    let rows = series.values().flat_map(|points| points.keys().copied());
+    let min_rows = rows.clone().min().unwrap() as f64;
+    let max_rows = rows.max().unwrap() as f64;
+    let max_ratio = series
+        .values()
+        .flat_map(|points| points.values().copied())
+        .fold(0.0_f64, f64::max);
+
+    let root = SVGBackend::new(path, SIZE).into_drawing_area();
+    root.fill(&WHITE).map_err(chart_error)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            format!("Compression ratio vs rows ({})", schema),
+            (FONT, 20),
+        )
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(
+            (min_rows / 2.0..max_rows * 2.0).log_scale(),
+            0.0..max_ratio * 1.1,
+        )
+        .map_err(chart_error)?;
+    chart
+        .configure_mesh()
+        .x_desc("rows")
+        .y_desc("logical bytes / file bytes")
+        .draw()
+        .map_err(chart_error)?;
+
+    for (i, (config, points)) in series.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        let points: Vec<(f64, f64)> = points.iter().map(|(&r, &v)| (r as f64, v)).collect();
+        chart
+            .draw_series(LineSeries::new(points.clone(), color.stroke_width(2)))
+            .map_err(chart_error)?
+            .label(config.as_str())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        chart
+            .draw_series(
+                points
+                    .into_iter()
+                    .map(|p| Circle::new(p, 3, color.filled())),
+            )
+            .map_err(chart_error)?;
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(chart_error)?;
+    root.present().map_err(chart_error)?;
+    Ok(true)
+}
+
+// File size against full-scan throughput at the largest row count. Smaller
+// and faster is better, so the Pareto frontier runs from the bottom left.
+fn size_vs_scan(path: &Path, schema: &str, records: &[&Record]) -> Result<bool> {
+    let scans: Vec<&Record> = records
+        .iter()
+        .copied()
+        .filter(|r| r.step == "scan" && r.throughput_mbps.is_some() && r.file_bytes.is_some())
+        .filter(|r| matches!(r.columns.as_deref(), None | Some("all")))
+        .collect();
+    let Some(rows) = scans.iter().map(|r| r.rows).max() else {
+        return Ok(false);
+    };
+    let points: Vec<(String, f64, f64)> = scans
+        .iter()
+        .filter(|r| r.rows == rows)
+        .map(|r| {
+            (
+                series_label(r),
+                r.file_bytes.unwrap() as f64,
+                r.throughput_mbps.unwrap(),
+            )
+        })
+        .collect();
+
+    let mut frontier: Vec<(f64, f64)> = Vec::new();
+    let mut sorted = points.clone();
+    sorted.sort_by(|a, b| a.1.total_cmp(&b.1).then(b.2.total_cmp(&a.2)));
+    for &(_, size, throughput) in &sorted {
+        if frontier.last().map_or(true, |&(_, best)| throughput > best) {
+            frontier.push((size, throughput));
+        }
+    }
+
+    let max_size = points.iter().map(|p| p.1).fold(0.0_f64, f64::max);
+    let max_throughput = points.iter().map(|p| p.2).fold(0.0_f64, f64::max);
+
+    let root = SVGBackend::new(path, SIZE).into_drawing_area();
+    root.fill(&WHITE).map_err(chart_error)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            format!("File size vs scan throughput ({}, {} rows)", schema, rows),
+            (FONT, 20),
+        )
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0.0..max_size * 1.15, 0.0..max_throughput * 1.15)
+        .map_err(chart_error)?;
+    chart
+        .configure_mesh()
+        .x_desc("file bytes")
+        .y_desc("scan MB/s")
+        .draw()
+        .map_err(chart_error)?;
+
+    chart
+        .draw_series(LineSeries::new(frontier.clone(), RED.stroke_width(2)))
+        .map_err(chart_error)?
+        .label("Pareto frontier")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    for (i, (config, size, throughput)) in points.into_iter().enumerate() {
+        let on_frontier = frontier.contains(&(size, throughput));
+        let color = Palette99::pick(i).to_rgba();
+        let radius = if on_frontier { 6 } else { 4 };
+        chart
+            .draw_series([Circle::new((size, throughput), radius, color.filled())])
+            .map_err(chart_error)?;
+        chart
+            .draw_series([Text::new(
+                config,
+                (size, throughput),
+                (FONT, 13).into_font(),
+            )])
+            .map_err(chart_error)?;
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(chart_error)?;
+    root.present().map_err(chart_error)?;
+    Ok(true)
+}
+
+// Take latency per configuration at the largest row count: whiskers from min
+// to p99 around the median, and a box for the median's confidence interval
+fn take_latency(path: &Path, schema: &str, records: &[&Record]) -> Result<bool> {
+    // Full-width takes only, projected ones are a different experiment
+    let takes: Vec<&Record> = records
+        .iter()
+        .copied()
+        .filter(|r| r.step == "take" && r.timing.is_some())
+        .filter(|r| matches!(r.columns.as_deref(), None | Some("all")))
+        .collect();
+    let Some(rows) = takes.iter().map(|r| r.rows).max() else {
+        return Ok(false);
+    };
+    let mut timings = BTreeMap::new();
+    for record in takes.iter().filter(|r| r.rows == rows) {
+        timings.insert(series_label(record), record.timing.unwrap());
+    }
+    let names: Vec<String> = timings.keys().cloned().collect();
+    let max_ms = timings.values().map(|t| t.p99_ms).fold(0.0_f64, f64::max);
+
+    let root = SVGBackend::new(path, SIZE).into_drawing_area();
+    root.fill(&WHITE).map_err(chart_error)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            format!("Take latency ({}, {} rows)", schema, rows),
+            (FONT, 20),
+        )
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(-0.5..names.len() as f64 - 0.5, 0.0..max_ms * 1.15)
+        .map_err(chart_error)?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(names.len())
+        .x_label_formatter(&|x: &f64| names.get(x.round() as usize).cloned().unwrap_or_default())
+        .y_desc("ms (min / median / p99, box is 95% CI)")
+        .draw()
+        .map_err(chart_error)?;
+
+    for (i, timing) in timings.values().enumerate() {
+        let x = i as f64;
+        let color = Palette99::pick(i).to_rgba();
+        if let Some((low, high)) = timing.ci_low_ms.zip(timing.ci_high_ms) {
+            chart
+                .draw_series([Rectangle::new(
+                    [(x - 0.15, low), (x + 0.15, high)],
+                    color.mix(0.4).filled(),
+                )])
+                .map_err(chart_error)?;
+        }
+        chart
+            .draw_series([ErrorBar::new_vertical(
+                x,
+                timing.min_ms,
+                timing.median_ms,
+                timing.p99_ms,
+                color.stroke_width(2),
+                20,
+            )])
+            .map_err(chart_error)?;
+    }
+    root.present().map_err(chart_error)?;
+    Ok(true)
+}
@@ -1,4 +1,5 @@
-use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StructArray};
+use arrow::buffer::NullBuffer;
+use arrow_array::{Array, ArrayRef, Float64Array, Int64Array, RecordBatch, StructArray};
 use arrow_schema::{DataType, Field, Fields, Schema};
 use rand::distributions::{Distribution, WeightedIndex};
 use rand::rngs::StdRng;
@@ -6,45 +7,223 @@ use rand::{Rng, SeedableRng};
 use std::sync::Arc;
 
 const NUM_FEATURES: usize = 3827;
+pub const DEFAULT_SEED: u64 = 42;
+
+// The generated schemas, selectable by name from the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    Nested,
+    Flat,
+    Nullable,
+}
+
+impl SchemaKind {
+    pub const ALL: [SchemaKind; 3] = [SchemaKind::Nested, SchemaKind::Flat, SchemaKind::Nullable];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SchemaKind::Nested => "nested",
+            SchemaKind::Flat => "flat",
+            SchemaKind::Nullable => "nullable",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SchemaKind::Nested => {
+                "Nested Schema: uuid (int64) + features (struct with 3827 double fields)"
+            }
+            SchemaKind::Flat => "Flat Schema: uuid (int64) + 3827 double columns",
+            SchemaKind::Nullable => {
+                "Nullable Schema: uuid (int64) + features (nullable struct with 3827 nullable double fields)"
+            }
+        }
+    }
+
+    // What the generated features hold under `distribution`
+    pub fn pattern(&self, distribution: ValueDistribution) -> String {
+        match self {
+            SchemaKind::Nullable => format!(
+                "{}; 10% of values and 5% of rows null",
+                distribution.description()
+            ),
+            _ => distribution.description().to_string(),
+        }
+    }
+
+    pub fn generate(&self, num_rows: usize, seed: u64) -> RecordBatch {
+        self.generate_with(num_rows, seed, ValueDistribution::Mixed)
+    }
+
+    pub fn generate_with(
+        &self,
+        num_rows: usize,
+        seed: u64,
+        distribution: ValueDistribution,
+    ) -> RecordBatch {
+        match self {
+            SchemaKind::Nested => generate_nested_record_batch_with(num_rows, seed, distribution),
+            SchemaKind::Flat => generate_flat_record_batch_with(num_rows, seed, distribution),
+            SchemaKind::Nullable => {
+                generate_nullable_record_batch_with(num_rows, seed, distribution)
+            }
+        }
+    }
+}
+
+// How feature values are drawn. `Mixed` is the pattern every report was
+// built on; the others bracket it for encodings that depend on repetition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueDistribution {
+    // 40% zeros, 40% common values, 20% random values
+    #[default]
+    Mixed,
+    // Values from `Mixed`, each repeated for a run of 1 to 64 rows
+    Runs,
+    // Uniform in -1000..1000, no repetition to exploit
+    Random,
+    // Every value is zero
+    Constant,
+}
+
+impl ValueDistribution {
+    pub const ALL: [ValueDistribution; 4] = [
+        ValueDistribution::Mixed,
+        ValueDistribution::Runs,
+        ValueDistribution::Random,
+        ValueDistribution::Constant,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueDistribution::Mixed => "mixed",
+            ValueDistribution::Runs => "runs",
+            ValueDistribution::Random => "random",
+            ValueDistribution::Constant => "constant",
+        }
+    }
+
+    // How values are drawn, for report headers
+    pub fn description(&self) -> &'static str {
+        match self {
+            ValueDistribution::Mixed => "32% zeros, 48% other common values, 20% random values",
+            ValueDistribution::Runs => "mixed values repeated in runs of 1 to 64 rows",
+            ValueDistribution::Random => "uniform random values, no repetition",
+            ValueDistribution::Constant => "every value zero",
+        }
+    }
+}
+
+impl std::str::FromStr for ValueDistribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ValueDistribution::ALL
+            .into_iter()
+            .find(|distribution| distribution.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown distribution `{}`, expected mixed, runs, random or constant",
+                    s
+                )
+            })
+    }
+}
+
+// Draws the values of one feature column. `Mixed` consumes the rng exactly
+// like the original generators did, so seeded batches are unchanged.
+struct Sampler {
+    distribution: ValueDistribution,
+    dist: WeightedIndex<i32>,
+    run_left: usize,
+    current: f64,
+}
+
+impl Sampler {
+    // Define common values with many zeros (simulating real data)
+    const COMMON_VALUES: [f64; 10] = [0.0, 1.0, -1.0, 2.0, -2.0, 5.0, -5.0, 10.0, -10.0, 100.0];
+    const WEIGHTS: [i32; 10] = [40, 15, 15, 8, 8, 4, 4, 3, 2, 1]; // 40% zeros
+
+    fn new(distribution: ValueDistribution) -> Self {
+        Sampler {
+            distribution,
+            dist: WeightedIndex::new(Self::WEIGHTS).unwrap(),
+            run_left: 0,
+            current: 0.0,
+        }
+    }
+
+    fn mixed(&self, rng: &mut StdRng) -> f64 {
+        // 80% chance of using common values (includes many zeros)
+        if rng.gen_bool(0.8) {
+            Self::COMMON_VALUES[self.dist.sample(rng)]
+        } else {
+            // 20% chance of random value
+            rng.gen_range(-1000.0..1000.0)
+        }
+    }
+
+    fn next(&mut self, rng: &mut StdRng) -> f64 {
+        match self.distribution {
+            ValueDistribution::Mixed => self.mixed(rng),
+            ValueDistribution::Runs => {
+                if self.run_left == 0 {
+                    self.current = self.mixed(rng);
+                    self.run_left = rng.gen_range(1..=64);
+                }
+                self.run_left -= 1;
+                self.current
+            }
+            ValueDistribution::Random => rng.gen_range(-1000.0..1000.0),
+            ValueDistribution::Constant => 0.0,
+        }
+    }
+}
+
+impl std::str::FromStr for SchemaKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SchemaKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| format!("unknown schema `{}`, expected nested, flat or nullable", s))
+    }
+}
+
 
 pub fn generate_record_batch(num_rows: usize) -> RecordBatch {
     generate_nested_record_batch(num_rows)
 }
 
 pub fn generate_nested_record_batch(num_rows: usize) -> RecordBatch {
-    let mut rng = StdRng::seed_from_u64(42);
+    generate_nested_record_batch_with_seed(num_rows, DEFAULT_SEED)
+}
+
+pub fn generate_nested_record_batch_with_seed(num_rows: usize, seed: u64) -> RecordBatch {
+    generate_nested_record_batch_with(num_rows, seed, ValueDistribution::Mixed)
+}
+
+pub fn generate_nested_record_batch_with(
+    num_rows: usize,
+    seed: u64,
+    distribution: ValueDistribution,
+) -> RecordBatch {
+    let mut rng = StdRng::seed_from_u64(seed);
     
     // Generate UUID column
     let uuids: Vec<i64> = (0..num_rows as i64).collect();
     let uuid_array = Arc::new(Int64Array::from(uuids)) as ArrayRef;
     
     // Generate features struct
-    let mut feature_arrays: Vec<ArrayRef> = Vec::with_capacity(NUM_FEATURES);
-    let mut feature_fields: Vec<Field> = Vec::with_capacity(NUM_FEATURES);
-    
-    // Define common values with many zeros (simulating real data)
-    let common_values = [0.0, 1.0, -1.0, 2.0, -2.0, 5.0, -5.0, 10.0, -10.0, 100.0];
-    let weights = [40, 15, 15, 8, 8, 4, 4, 3, 2, 1]; // 40% zeros
-    let dist = WeightedIndex::new(&weights).unwrap();
-    
-    // Generate data for each feature
-    for i in 0..NUM_FEATURES {
-        let mut feature_values = Vec::with_capacity(num_rows);
-        
-        for _ in 0..num_rows {
-            // 80% chance of using common values (includes many zeros)
-            let val = if rng.gen_bool(0.8) {
-                common_values[dist.sample(&mut rng)]
-            } else {
-                // 20% chance of random value
-                rng.gen_range(-1000.0..1000.0)
-            };
-            feature_values.push(val);
-        }
-        
-        feature_arrays.push(Arc::new(Float64Array::from(feature_values)) as ArrayRef);
-        feature_fields.push(Field::new(format!("feature{}", i), DataType::Float64, false));
-    }
+    let (feature_fields, feature_arrays) = sample_features(
+        &mut rng,
+        num_rows,
+        NUM_FEATURES,
+        "feature",
+        distribution,
+        None,
+    );
     
     // Clone fields for schema before creating struct array
     let schema_fields = feature_fields.clone();
@@ -71,42 +250,155 @@ pub fn generate_nested_record_batch(num_rows: usize) -> RecordBatch {
 }
 
 pub fn generate_flat_record_batch(num_rows: usize) -> RecordBatch {
-    let mut rng = StdRng::seed_from_u64(42);
+    generate_flat_record_batch_with_seed(num_rows, DEFAULT_SEED)
+}
+
+pub fn generate_flat_record_batch_with_seed(num_rows: usize, seed: u64) -> RecordBatch {
+    generate_flat_record_batch_with(num_rows, seed, ValueDistribution::Mixed)
+}
+
+pub fn generate_flat_record_batch_with(
+    num_rows: usize,
+    seed: u64,
+    distribution: ValueDistribution,
+) -> RecordBatch {
+    let mut rng = StdRng::seed_from_u64(seed);
     
     // Generate UUID column
     let uuids: Vec<i64> = (0..num_rows as i64).collect();
     let uuid_array = Arc::new(Int64Array::from(uuids)) as ArrayRef;
     
-    // Generate feature columns
+    // Generate feature columns, each a separate column
+    let (feature_fields, feature_arrays) = sample_features(
+        &mut rng,
+        num_rows,
+        NUM_FEATURES,
+        "feature",
+        distribution,
+        None,
+    );
     let mut columns: Vec<ArrayRef> = vec![uuid_array];
     let mut fields: Vec<Field> = vec![Field::new("uuid", DataType::Int64, false)];
-    
-    // Define common values with many zeros (simulating real data)
-    let common_values = [0.0, 1.0, -1.0, 2.0, -2.0, 5.0, -5.0, 10.0, -10.0, 100.0];
-    let weights = [40, 15, 15, 8, 8, 4, 4, 3, 2, 1]; // 40% zeros
-    let dist = WeightedIndex::new(&weights).unwrap();
-    
-    // Generate data for each feature as a separate column
-    for i in 0..NUM_FEATURES {
-        let mut feature_values = Vec::with_capacity(num_rows);
-        
-        for _ in 0..num_rows {
-            // 80% chance of using common values (includes many zeros)
-            let val = if rng.gen_bool(0.8) {
-                common_values[dist.sample(&mut rng)]
-            } else {
-                // 20% chance of random value
-                rng.gen_range(-1000.0..1000.0)
-            };
-            feature_values.push(val);
-        }
-        
-        columns.push(Arc::new(Float64Array::from(feature_values)) as ArrayRef);
-        fields.push(Field::new(format!("feature{}", i), DataType::Float64, false));
-    }
+    columns.extend(feature_arrays);
+    fields.extend(feature_fields);
     
     // Create the final schema with flat structure
     let schema = Arc::new(Schema::new(fields));
     
     RecordBatch::try_new(schema, columns).unwrap()
-}
\ No newline at end of file
+}
+
+// `count` top-level feature columns named `{prefix}{i}`, drawn like the
+// generated features, for tables that grow new columns
+pub fn generate_feature_columns(
+    num_rows: usize,
+    count: usize,
+    prefix: &str,
+    seed: u64,
+    distribution: ValueDistribution,
+) -> RecordBatch {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (fields, columns) = sample_features(&mut rng, num_rows, count, prefix, distribution, None);
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+}
+
+pub fn generate_nullable_record_batch(num_rows: usize) -> RecordBatch {
+    generate_nullable_record_batch_with_seed(num_rows, DEFAULT_SEED)
+}
+
+pub fn generate_nullable_record_batch_with_seed(num_rows: usize, seed: u64) -> RecordBatch {
+    generate_nullable_record_batch_with(num_rows, seed, ValueDistribution::Mixed)
+}
+
+pub fn generate_nullable_record_batch_with(
+    num_rows: usize,
+    seed: u64,
+    distribution: ValueDistribution,
+) -> RecordBatch {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // Generate UUID column
+    let uuids: Vec<i64> = (0..num_rows as i64).collect();
+    let uuid_array = Arc::new(Int64Array::from(uuids)) as ArrayRef;
+
+    // 10% of values are missing, and 5% of rows have no features at all
+    let (feature_fields, feature_arrays) = sample_features(
+        &mut rng,
+        num_rows,
+        NUM_FEATURES,
+        "feature",
+        distribution,
+        Some(0.1),
+    );
+    let validity: NullBuffer = (0..num_rows).map(|_| !rng.gen_bool(0.05)).collect();
+
+    // Children are null wherever the struct is, so readers that push struct
+    // nulls down into the children decode the same values
+    let feature_arrays: Vec<ArrayRef> = feature_arrays
+        .iter()
+        .map(|array| {
+            let values = array.as_any().downcast_ref::<Float64Array>().unwrap();
+            let nulls = NullBuffer::union(values.nulls(), Some(&validity));
+            Arc::new(Float64Array::new(values.values().clone(), nulls)) as ArrayRef
+        })
+        .collect();
+    let features_struct = Arc::new(
+        StructArray::try_new(
+            Fields::from(feature_fields.clone()),
+            feature_arrays,
+            Some(validity),
+        )
+        .unwrap(),
+    ) as ArrayRef;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("uuid", DataType::Int64, false),
+        Field::new(
+            "features",
+            DataType::Struct(Fields::from(feature_fields)),
+            true,
+        ),
+    ]));
+
+    RecordBatch::try_new(schema, vec![uuid_array, features_struct]).unwrap()
+}
+
+// `count` feature columns named `{prefix}{i}`, drawn one after another from
+// `rng`. With `null_chance`, every value first gets that chance of being
+// missing and the fields are nullable.
+fn sample_features(
+    rng: &mut StdRng,
+    num_rows: usize,
+    count: usize,
+    prefix: &str,
+    distribution: ValueDistribution,
+    null_chance: Option<f64>,
+) -> (Vec<Field>, Vec<ArrayRef>) {
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(count);
+    let mut fields: Vec<Field> = Vec::with_capacity(count);
+    for i in 0..count {
+        let mut sampler = Sampler::new(distribution);
+        let array = match null_chance {
+            None => {
+                let values: Vec<f64> = (0..num_rows).map(|_| sampler.next(rng)).collect();
+                Float64Array::from(values)
+            }
+            Some(chance) => (0..num_rows)
+                .map(|_| {
+                    if rng.gen_bool(chance) {
+                        None
+                    } else {
+                        Some(sampler.next(rng))
+                    }
+                })
+                .collect(),
+        };
+        arrays.push(Arc::new(array) as ArrayRef);
+        fields.push(Field::new(
+            format!("{}{}", prefix, i),
+            DataType::Float64,
+            null_chance.is_some(),
+        ));
+    }
+    (fields, arrays)
+}
@@ -0,0 +1,258 @@
+use crate::error::{Error, Result};
+use crate::lance::with_compression;
+use ::lance::dataset::optimize::{compact_files, CompactionOptions};
+use ::lance::dataset::{Dataset, NewColumnTransform, ProjectionRequest, WriteMode, WriteParams};
+use arrow_array::{RecordBatch, RecordBatchIterator};
+use futures::TryStreamExt;
+use lance_encoding::version::LanceFileVersion;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Layout knobs passed through to `WriteParams`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatasetOptions {
+    pub max_rows_per_file: usize,
+    pub max_rows_per_group: usize,
+}
+
+impl Default for DatasetOptions {
+    fn default() -> Self {
+        let params = WriteParams::default();
+        DatasetOptions {
+            max_rows_per_file: params.max_rows_per_file,
+            max_rows_per_group: params.max_rows_per_group,
+        }
+    }
+}
+
+// A dataset directory under the system temp dir, removed on drop. Datasets
+// go to local disk rather than memory so manifests and versions are real
+// files that can be measured.
+#[derive(Debug)]
+pub struct DatasetDir {
+    path: PathBuf,
+}
+
+impl DatasetDir {
+    pub fn new() -> Result<DatasetDir> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "lance-rle-benchmark-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        if path.exists() {
+            fs::remove_dir_all(&path)?;
+        }
+        Ok(DatasetDir { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn uri(&self) -> &str {
+        self.path.to_str().expect("temp dir paths are UTF-8")
+    }
+}
+
+impl Drop for DatasetDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// Bytes on disk by what they hold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub data: u64,
+    pub deletions: u64,
+    // Manifests, one per version
+    pub manifests: u64,
+    pub transactions: u64,
+    pub other: u64,
+    pub files: u64,
+}
+
+impl DiskUsage {
+    pub fn total(&self) -> u64 {
+        self.data + self.deletions + self.manifests + self.transactions + self.other
+    }
+
+    // Everything that isn't data or deletion files
+    pub fn metadata(&self) -> u64 {
+        self.manifests + self.transactions + self.other
+    }
+}
+
+pub fn disk_usage(dir: &Path) -> Result<DiskUsage> {
+    let mut usage = DiskUsage::default();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+                continue;
+            }
+            let bytes = metadata.len();
+            usage.files += 1;
+            // The top-level directory says what a file is
+            let relative = entry.path();
+            let top = relative
+                .strip_prefix(dir)
+                .ok()
+                .and_then(|p| p.components().next())
+                .map(|c| c.as_os_str().to_string_lossy().into_owned());
+            match top.as_deref() {
+                Some("data") => usage.data += bytes,
+                Some("_deletions") => usage.deletions += bytes,
+                Some("_versions") => usage.manifests += bytes,
+                Some("_transactions") => usage.transactions += bytes,
+                _ => usage.other += bytes,
+            }
+        }
+    }
+    Ok(usage)
+}
+
+pub fn write_params(
+    version: LanceFileVersion,
+    options: DatasetOptions,
+    mode: WriteMode,
+) -> WriteParams {
+    WriteParams {
+        max_rows_per_file: options.max_rows_per_file,
+        max_rows_per_group: options.max_rows_per_group,
+        data_storage_version: Some(version),
+        mode,
+        ..Default::default()
+    }
+}
+
+// Write `batches` as a new dataset at `uri`, or add them to it with
+// `WriteMode::Append`, requesting `compression` on every field
+pub async fn write(
+    batches: &[RecordBatch],
+    uri: &str,
+    version: LanceFileVersion,
+    compression: &str,
+    options: DatasetOptions,
+    mode: WriteMode,
+) -> Result<Dataset> {
+    let Some(first) = batches.first() else {
+        return Err(Error::InvalidInput("no batches to write".to_string()));
+    };
+    let schema = with_compression(first, compression)?.schema();
+    let batches = batches
+        .iter()
+        .map(|batch| with_compression(batch, compression))
+        .collect::<Result<Vec<_>>>()?;
+    let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+    let params = write_params(version, options, mode);
+    Ok(Dataset::write(reader, uri, Some(params)).await?)
+}
+
+// Add the columns of `columns`, one row per dataset row in order, writing
+// them as new data files next to the existing ones
+pub async fn add_columns(uri: &str, columns: &RecordBatch, compression: &str) -> Result<()> {
+    let columns = with_compression(columns, compression)?;
+    let schema = columns.schema();
+    let reader = RecordBatchIterator::new([Ok(columns)], schema);
+    let mut dataset = open(uri).await?;
+    dataset
+        .add_columns(NewColumnTransform::Reader(Box::new(reader)), None, None)
+        .await?;
+    Ok(())
+}
+
+// Reads open the dataset themselves, so every measurement includes loading
+// the latest manifest, as it does for a client
+pub async fn open(uri: &str) -> Result<Dataset> {
+    Ok(Dataset::open(uri).await?)
+}
+
+// Scan `columns`, or every column if empty, and return the row count
+pub async fn scan(uri: &str, columns: &[String]) -> Result<usize> {
+    let dataset = open(uri).await?;
+    let mut scanner = dataset.scan();
+    if !columns.is_empty() {
+        scanner.project(columns)?;
+    }
+    let batches: Vec<RecordBatch> = scanner.try_into_stream().await?.try_collect().await?;
+    Ok(batches.iter().map(RecordBatch::num_rows).sum())
+}
+
+fn projection(dataset: &Dataset, columns: &[String]) -> ProjectionRequest {
+    if columns.is_empty() {
+        ProjectionRequest::from(dataset.schema().clone())
+    } else {
+        ProjectionRequest::from_columns(columns, dataset.schema())
+    }
+}
+
+// Take rows by their position in the dataset
+pub async fn take(uri: &str, offsets: &[u64], columns: &[String]) -> Result<RecordBatch> {
+    let dataset = open(uri).await?;
+    let projection = projection(&dataset, columns);
+    Ok(dataset.take(offsets, projection).await?)
+}
+
+// Take rows by row id, see `row_ids`
+pub async fn take_rows(uri: &str, row_ids: &[u64], columns: &[String]) -> Result<RecordBatch> {
+    let dataset = open(uri).await?;
+    let projection = projection(&dataset, columns);
+    Ok(dataset.take_rows(row_ids, projection).await?)
+}
+
+// Row ids of the rows at `offsets`: the fragment id in the upper 32 bits and
+// the row's offset within the fragment in the lower ones. Offsets count
+// physical rows, deleted ones included; one past every fragment is an
+// error rather than a row quietly left out.
+pub fn row_ids(dataset: &Dataset, offsets: &[u64]) -> Result<Vec<u64>> {
+    let mut fragments = Vec::new();
+    let mut start = 0;
+    for fragment in dataset.get_fragments() {
+        let rows = fragment.metadata().physical_rows.unwrap_or(0) as u64;
+        fragments.push((start, rows, fragment.id() as u64));
+        start += rows;
+    }
+    offsets
+        .iter()
+        .map(|&offset| {
+            fragments
+                .iter()
+                .find(|(start, rows, _)| offset >= *start && offset < start + rows)
+                .map(|(start, _, id)| (id << 32) | (offset - start))
+                .ok_or_else(|| {
+                    Error::InvalidInput(format!(
+                        "row {} is out of range for {} rows",
+                        offset, start
+                    ))
+                })
+        })
+        .collect()
+}
+
+// Rewrite small fragments into larger ones with Lance's default options and
+// return (fragments removed, fragments added). The old files stay on disk
+// for earlier versions.
+pub async fn compact(uri: &str) -> Result<(usize, usize)> {
+    let mut dataset = open(uri).await?;
+    let metrics = compact_files(&mut dataset, CompactionOptions::default(), None).await?;
+    Ok((metrics.fragments_removed, metrics.fragments_added))
+}
+
+// Bytes of the data files the latest version reads, leaving out files only
+// earlier versions still refer to
+pub fn live_data_bytes(dataset: &Dataset, dir: &Path) -> Result<u64> {
+    let mut bytes = 0;
+    for fragment in dataset.get_fragments() {
+        for file in &fragment.metadata().files {
+            bytes += fs::metadata(dir.join("data").join(&file.path))?.len();
+        }
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,98 @@
+use crate::verify::Mismatch;
+use std::fmt;
+
+// Everything that can go wrong while writing, reading or checking a file.
+#[derive(Debug)]
+pub enum Error {
+    Lance(lance_core::Error),
+    Parquet(parquet::errors::ParquetError),
+    Arrow(arrow_schema::ArrowError),
+    ObjectStore(object_store::Error),
+    Io(std::io::Error),
+    // Decoded data differs from what was written
+    Mismatch(Mismatch),
+    // The caller asked for something the adapters can't do
+    InvalidInput(String),
+    // Experiment config files that don't parse
+    Config(toml::de::Error),
+    // plotters errors are generic over the backend, so only the message is kept
+    Chart(String),
+    // File metadata that doesn't add up, e.g. sections larger than the file
+    Layout(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Lance(e) => write!(f, "lance: {}", e),
+            Error::Parquet(e) => write!(f, "parquet: {}", e),
+            Error::Arrow(e) => write!(f, "arrow: {}", e),
+            Error::ObjectStore(e) => write!(f, "object store: {}", e),
+            Error::Io(e) => write!(f, "io: {}", e),
+            Error::Mismatch(e) => write!(f, "round-trip mismatch: {}", e),
+            Error::InvalidInput(message) => write!(f, "invalid input: {}", message),
+            Error::Config(e) => write!(f, "config: {}", e),
+            Error::Chart(message) => write!(f, "chart: {}", message),
+            Error::Layout(message) => write!(f, "layout: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Lance(e) => Some(e),
+            Error::Parquet(e) => Some(e),
+            Error::Arrow(e) => Some(e),
+            Error::ObjectStore(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Mismatch(e) => Some(e),
+            Error::Config(e) => Some(e),
+            Error::InvalidInput(_) | Error::Chart(_) | Error::Layout(_) => None,
+        }
+    }
+}
+
+impl From<lance_core::Error> for Error {
+    fn from(e: lance_core::Error) -> Self {
+        Error::Lance(e)
+    }
+}
+
+impl From<parquet::errors::ParquetError> for Error {
+    fn from(e: parquet::errors::ParquetError) -> Self {
+        Error::Parquet(e)
+    }
+}
+
+impl From<arrow_schema::ArrowError> for Error {
+    fn from(e: arrow_schema::ArrowError) -> Self {
+        Error::Arrow(e)
+    }
+}
+
+impl From<object_store::Error> for Error {
+    fn from(e: object_store::Error) -> Self {
+        Error::ObjectStore(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<Mismatch> for Error {
+    fn from(e: Mismatch) -> Self {
+        Error::Mismatch(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Config(e)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::error::Result;
+use crate::stats::ReadStats;
+use crate::{lance, parquet};
+use ::parquet::basic::{Compression, ZstdLevel};
+use arrow_array::RecordBatch;
+use lance_encoding::version::LanceFileVersion;
+use std::str::FromStr;
+
+// A single file format configuration under benchmark. Every runner and test
+// drives the adapters through this so they all cover the same matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatConfig {
+    Lance {
+        version: LanceFileVersion,
+        compression: LanceCompression,
+    },
+    Parquet {
+        compression: Compression,
+    },
+}
+
+// Value requested through `COMPRESSION_META_KEY` on every field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanceCompression {
+    Bitpacking,
+    Rle,
+}
+
+impl LanceCompression {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LanceCompression::Bitpacking => "bitpacking",
+            LanceCompression::Rle => "rle",
+        }
+    }
+}
+
+impl FromStr for LanceCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bitpacking" => Ok(LanceCompression::Bitpacking),
+            "rle" => Ok(LanceCompression::Rle),
+            _ => Err(format!(
+                "unknown lance compression `{}`, expected bitpacking or rle",
+                s
+            )),
+        }
+    }
+}
+
+impl FormatConfig {
+    pub const LANCE_BITPACKING: FormatConfig = FormatConfig::Lance {
+        version: LanceFileVersion::V2_1,
+        compression: LanceCompression::Bitpacking,
+    };
+    pub const LANCE_RLE: FormatConfig = FormatConfig::Lance {
+        version: LanceFileVersion::V2_1,
+        compression: LanceCompression::Rle,
+    };
+    pub const PARQUET: FormatConfig = FormatConfig::Parquet {
+        compression: Compression::SNAPPY,
+    };
+
+    // Every format/compression/version combination we know how to write
+    pub fn all() -> Vec<FormatConfig> {
+        let mut configs = Vec::new();
+        for version in [LanceFileVersion::V2_0, LanceFileVersion::V2_1] {
+            for compression in [LanceCompression::Bitpacking, LanceCompression::Rle] {
+                configs.push(FormatConfig::Lance {
+                    version,
+                    compression,
+                });
+            }
+        }
+        for compression in [
+            Compression::UNCOMPRESSED,
+            Compression::SNAPPY,
+            Compression::ZSTD(ZstdLevel::default()),
+        ] {
+            configs.push(FormatConfig::Parquet { compression });
+        }
+        configs
+    }
+
+    pub fn format(&self) -> &'static str {
+        match self {
+            FormatConfig::Lance { .. } => "lance",
+            FormatConfig::Parquet { .. } => "parquet",
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            FormatConfig::Lance {
+                version,
+                compression,
+            } => format!("lance-v{}-{}", version, compression.as_str()),
+            FormatConfig::Parquet { compression } => {
+                let codec = match compression {
+                    Compression::UNCOMPRESSED => "none",
+                    Compression::SNAPPY => "snappy",
+                    Compression::ZSTD(_) => "zstd",
+                    Compression::LZ4_RAW => "lz4",
+                    _ => "other",
+                };
+                format!("parquet-{}", codec)
+            }
+        }
+    }
+
+    pub async fn write(&self, batch: RecordBatch) -> Result<Vec<u8>> {
+        self.write_with(batch, None).await
+    }
+
+    // `page_size` caps Lance pages and Parquet data pages, in bytes
+    pub async fn write_with(&self, batch: RecordBatch, page_size: Option<u64>) -> Result<Vec<u8>> {
+        match self {
+            FormatConfig::Lance {
+                version,
+                compression,
+            } => lance::write_bytes_with(batch, *version, compression.as_str(), page_size).await,
+            FormatConfig::Parquet { compression } => {
+                parquet::write_bytes_with(batch, *compression, page_size.map(|size| size as usize))
+            }
+        }
+    }
+
+    pub async fn read(&self, bytes: &[u8]) -> Result<Vec<RecordBatch>> {
+        match self {
+            FormatConfig::Lance { .. } => lance::read_bytes(bytes).await,
+            FormatConfig::Parquet { .. } => parquet::read_bytes(bytes),
+        }
+    }
+
+    pub async fn take(&self, bytes: &[u8], indices: &[usize]) -> Result<RecordBatch> {
+        match self {
+            FormatConfig::Lance { .. } => lance::take_rows_from_bytes(bytes, indices).await,
+            FormatConfig::Parquet { .. } => parquet::take_rows_from_bytes(bytes, indices),
+        }
+    }
+
+    pub async fn read_projected(
+        &self,
+        bytes: &[u8],
+        columns: &[String],
+    ) -> Result<(Vec<RecordBatch>, ReadStats)> {
+        match self {
+            FormatConfig::Lance { .. } => lance::read_bytes_projected(bytes, columns).await,
+            FormatConfig::Parquet { .. } => parquet::read_bytes_projected(bytes, columns),
+        }
+    }
+
+    pub async fn take_projected(
+        &self,
+        bytes: &[u8],
+        indices: &[usize],
+        columns: &[String],
+    ) -> Result<(RecordBatch, ReadStats)> {
+        match self {
+            FormatConfig::Lance { .. } => lance::take_rows_projected(bytes, indices, columns).await,
+            FormatConfig::Parquet { .. } => parquet::take_rows_projected(bytes, indices, columns),
+        }
+    }
+}
+
+// Accepts the names printed by `name`, plus the short forms `lance`,
+// `lance-rle` and `parquet` for the default Lance version and Parquet codec.
+impl FromStr for FormatConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('-');
+        match parts.next() {
+            Some("lance") => {
+                let mut version = LanceFileVersion::V2_1;
+                let mut compression = LanceCompression::Bitpacking;
+                for part in parts {
+                    match part.strip_prefix('v') {
+                        Some(v) => {
+                            version = v
+                                .parse()
+                                .map_err(|_| format!("unknown lance version `{}` in `{}`", v, s))?
+                        }
+                        None => compression = part.parse()?,
+                    }
+                }
+                Ok(FormatConfig::Lance {
+                    version,
+                    compression,
+                })
+            }
+            Some("parquet") => {
+                let compression = match parts.next().unwrap_or("snappy") {
+                    "none" => Compression::UNCOMPRESSED,
+                    "snappy" => Compression::SNAPPY,
+                    "zstd" => Compression::ZSTD(ZstdLevel::default()),
+                    "lz4" => Compression::LZ4_RAW,
+                    codec => {
+                        return Err(format!(
+                            "unknown parquet codec `{}`, expected none, snappy, zstd or lz4",
+                            codec
+                        ))
+                    }
+                };
+                Ok(FormatConfig::Parquet { compression })
+            }
+            _ => Err(format!(
+                "unknown format `{}`, expected e.g. lance-v2.1-rle or parquet-zstd",
+                s
+            )),
+        }
+    }
+}
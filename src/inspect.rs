@@ -0,0 +1,178 @@
+use crate::error::{Error, Result};
+use crate::format::FormatConfig;
+use crate::{lance, parquet};
+
+// Shape of a written file as reported by its own metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSummary {
+    pub file_bytes: usize,
+    pub num_rows: u64,
+    pub num_columns: usize,
+    // Lance pages, or Parquet column chunks
+    pub num_chunks: usize,
+}
+
+// Compressed bytes of one leaf column, summed over pages or row groups
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSize {
+    pub column: String,
+    pub compressed_bytes: u64,
+    // Parquet records this per chunk, Lance doesn't
+    pub uncompressed_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSizes {
+    pub file_bytes: u64,
+    pub columns: Vec<ColumnSize>,
+}
+
+impl ColumnSizes {
+    // Everything that isn't column data: metadata, footers, padding
+    pub fn overhead_bytes(&self) -> u64 {
+        let data: u64 = self.columns.iter().map(|c| c.compressed_bytes).sum();
+        self.file_bytes.saturating_sub(data)
+    }
+
+    // Largest columns first
+    pub fn worst(&self, n: usize) -> Vec<&ColumnSize> {
+        let mut columns: Vec<&ColumnSize> = self.columns.iter().collect();
+        columns.sort_by(|a, b| b.compressed_bytes.cmp(&a.compressed_bytes));
+        columns.truncate(n);
+        columns
+    }
+
+    // Bytes of each top-level struct, its children and any column of its
+    // own together, e.g. `features` for every `features.*` column
+    pub fn struct_totals(&self) -> Vec<(String, u64)> {
+        let mut totals: Vec<(String, u64)> = Vec::new();
+        for column in &self.columns {
+            let Some((parent, _)) = column.column.split_once('.') else {
+                continue;
+            };
+            match totals.iter_mut().find(|(name, _)| name == parent) {
+                Some((_, bytes)) => *bytes += column.compressed_bytes,
+                None => totals.push((parent.to_string(), column.compressed_bytes)),
+            }
+        }
+        for (name, bytes) in &mut totals {
+            if let Some(own) = self.columns.iter().find(|c| &c.column == name) {
+                *bytes += own.compressed_bytes;
+            }
+        }
+        totals
+    }
+}
+
+// Where a file's bytes go, section by section. Sections group bytes by
+// kind, not by position: Parquet, for one, puts each chunk's dictionary page
+// ahead of its data pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLayout {
+    pub file_bytes: u64,
+    pub sections: Vec<(&'static str, u64)>,
+}
+
+impl FileLayout {
+    // Whatever the named sections don't cover is listed as `other`, so the
+    // sections always add up to the file size. Sections that add up to more
+    // than the file mean the metadata was misread and are an error.
+    pub fn new(file_bytes: u64, mut sections: Vec<(&'static str, u64)>) -> Result<FileLayout> {
+        let named: u64 = sections.iter().map(|(_, bytes)| bytes).sum();
+        if named > file_bytes {
+            return Err(Error::Layout(format!(
+                "sections add up to {} bytes in a {} byte file",
+                named, file_bytes
+            )));
+        }
+        if named < file_bytes {
+            sections.push(("other", file_bytes - named));
+        }
+        Ok(FileLayout {
+            file_bytes,
+            sections,
+        })
+    }
+
+    // Percentage of the file taken by `bytes`
+    pub fn share(&self, bytes: u64) -> f64 {
+        bytes as f64 * 100.0 / self.file_bytes as f64
+    }
+}
+
+pub async fn layout(config: FormatConfig, bytes: &[u8]) -> Result<FileLayout> {
+    match config {
+        FormatConfig::Lance { .. } => lance::layout(bytes).await,
+        FormatConfig::Parquet { .. } => parquet::layout(bytes),
+    }
+}
+
+// How one chunk of a column was encoded: a Lance page or a Parquet column
+// chunk (one per row group)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkEncoding {
+    pub column: String,
+    pub chunk: usize,
+    pub num_values: u64,
+    // Lance structural layout, e.g. `MiniBlockLayout`, or `legacy` for 2.0
+    // pages; always `column chunk` for Parquet
+    pub layout: String,
+    // Lance encodings in the page's tree, outermost first; Parquet encodings
+    pub encodings: Vec<String>,
+    // Parquet's codec for the whole chunk. Lance has none, compression is
+    // one of its encodings.
+    pub compression: Option<String>,
+    pub buffer_sizes: Vec<u64>,
+    // Lance: the full encoding tree; Parquet: page counts per encoding
+    pub detail: String,
+}
+
+impl ChunkEncoding {
+    pub fn signature(&self) -> String {
+        let signature = format!("{}: {}", self.layout, self.encodings.join(" > "));
+        match &self.compression {
+            Some(compression) => format!("{} ({})", signature, compression),
+            None => signature,
+        }
+    }
+}
+
+pub async fn encodings(config: FormatConfig, bytes: &[u8]) -> Result<Vec<ChunkEncoding>> {
+    match config {
+        FormatConfig::Lance { .. } => lance::encodings(bytes).await,
+        FormatConfig::Parquet { .. } => parquet::encodings(bytes),
+    }
+}
+
+pub async fn column_sizes(config: FormatConfig, bytes: &[u8]) -> Result<ColumnSizes> {
+    match config {
+        FormatConfig::Lance { .. } => lance::column_sizes(bytes).await,
+        FormatConfig::Parquet { .. } => parquet::column_sizes(bytes),
+    }
+}
+
+pub async fn summarize(config: FormatConfig, bytes: &[u8]) -> Result<FileSummary> {
+    match config {
+        FormatConfig::Lance { .. } => lance::summarize(bytes).await,
+        FormatConfig::Parquet { .. } => parquet::summarize(bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncovered_bytes_are_other() {
+        let layout = FileLayout::new(100, vec![("data pages", 90), ("footer", 8)]).unwrap();
+        assert_eq!(layout.sections.last(), Some(&("other", 2)));
+        let exact = FileLayout::new(98, vec![("data pages", 90), ("footer", 8)]).unwrap();
+        assert_eq!(exact.sections.len(), 2);
+    }
+
+    #[test]
+    fn sections_larger_than_the_file_are_an_error() {
+        let layout = FileLayout::new(90, vec![("data pages", 90), ("footer", 8)]);
+        assert!(matches!(layout, Err(Error::Layout(_))));
+    }
+}
@@ -1,9 +1,14 @@
+use crate::error::Result;
+use crate::inspect::{ChunkEncoding, ColumnSize, ColumnSizes, FileLayout, FileSummary};
+use crate::stats::ReadStats;
+use crate::take::TakePlan;
 use arrow_array::RecordBatch;
 use arrow_schema::{Field, Schema};
 use futures::StreamExt;
 use lance_core::cache::LanceCache;
-use lance_core::datatypes::{Schema as LanceSchema, COMPRESSION_META_KEY};
-use lance_encoding::decoder::{DecoderPlugins, FilterExpression};
+use lance_core::datatypes::{Field as LanceField, Schema as LanceSchema, COMPRESSION_META_KEY};
+use lance_encoding::decoder::{DecoderPlugins, FilterExpression, PageEncoding};
+use lance_encoding::format::pb;
 use lance_encoding::version::LanceFileVersion;
 use lance_file::v2::reader::{FileReader, FileReaderOptions, ReaderProjection};
 use lance_file::v2::writer::{FileWriter, FileWriterOptions};
@@ -15,10 +20,27 @@ use object_store::path::Path;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-pub async fn write_bytes(batch: RecordBatch, _use_v2: bool, use_rle: bool) -> Vec<u8> {
+pub async fn write_bytes(batch: RecordBatch, _use_v2: bool, use_rle: bool) -> Result<Vec<u8>> {
+    let compression = if use_rle { "rle" } else { "bitpacking" };
+    write_bytes_with(batch, LanceFileVersion::V2_1, compression, None).await
+}
+
+// Write `batch` as a single Lance file, requesting `compression` for every
+// top-level field through the field metadata.
+pub async fn write_bytes_with(
+    batch: RecordBatch,
+    version: LanceFileVersion,
+    compression: &str,
+    max_page_bytes: Option<u64>,
+) -> Result<Vec<u8>> {
+    let batch_with_compression = with_compression(&batch, compression)?;
+    write_annotated_bytes(batch_with_compression, version, max_page_bytes).await
+}
+
+// `batch` with `compression` requested on every top-level field
+pub fn with_compression(batch: &RecordBatch, compression: &str) -> Result<RecordBatch> {
     // Create schema with compression metadata
     let mut metadata = HashMap::new();
-    let compression = if use_rle { "rle" } else { "bitpacking" };
     metadata.insert(COMPRESSION_META_KEY.to_string(), compression.to_string());
 
     let fields: Vec<Field> = batch
@@ -35,9 +57,20 @@ pub async fn write_bytes(batch: RecordBatch, _use_v2: bool, use_rle: bool) -> Ve
         fields,
         batch.schema().metadata().clone(),
     ));
-    let lance_schema = LanceSchema::try_from(arrow_schema.as_ref()).unwrap();
-    let batch_with_compression =
-        RecordBatch::try_new(arrow_schema, batch.columns().to_vec()).unwrap();
+    Ok(RecordBatch::try_new(
+        arrow_schema,
+        batch.columns().to_vec(),
+    )?)
+}
+
+// Write `batch` keeping whatever field metadata its schema already carries,
+// e.g. a different compression per column
+pub async fn write_annotated_bytes(
+    batch: RecordBatch,
+    version: LanceFileVersion,
+    max_page_bytes: Option<u64>,
+) -> Result<Vec<u8>> {
+    let lance_schema = LanceSchema::try_from(batch.schema().as_ref())?;
 
     // Use memory object store
     let object_store = Arc::new(ObjectStore::memory());
@@ -45,31 +78,28 @@ pub async fn write_bytes(batch: RecordBatch, _use_v2: bool, use_rle: bool) -> Ve
 
     // Write the file using v2 writer
     let options = FileWriterOptions {
-        format_version: Some(LanceFileVersion::V2_1),
+        format_version: Some(version),
+        // None keeps the writer's default page size
+        max_page_bytes,
         ..Default::default()
     };
 
     // Use custom encoding strategy for RLE
 
-    let object_writer = object_store.create(&path).await.unwrap();
-    let mut writer = FileWriter::try_new(object_writer, lance_schema, options).unwrap();
+    let object_writer = object_store.create(&path).await?;
+    let mut writer = FileWriter::try_new(object_writer, lance_schema, options)?;
 
-    writer.write_batch(&batch_with_compression).await.unwrap();
-    writer.finish().await.unwrap();
+    writer.write_batch(&batch).await?;
+    writer.finish().await?;
 
     // Read back the bytes
-    object_store
-        .inner
-        .get(&path)
-        .await
-        .unwrap()
-        .bytes()
-        .await
-        .unwrap()
-        .to_vec()
+    let bytes = object_store.inner.get(&path).await?.bytes().await?;
+    Ok(bytes.to_vec())
 }
 
-pub async fn read_bytes(bytes: &[u8]) -> Vec<RecordBatch> {
+// Load `bytes` into an in-memory store and open a v2 reader over them. The
+// scheduler is returned alongside so callers can inspect its I/O stats.
+pub(crate) async fn open_reader(bytes: &[u8]) -> Result<(FileReader, Arc<ScanScheduler>)> {
     // Use memory object store
     let object_store = Arc::new(ObjectStore::memory());
     let path = Path::from("data.lance");
@@ -78,8 +108,7 @@ pub async fn read_bytes(bytes: &[u8]) -> Vec<RecordBatch> {
     object_store
         .inner
         .put(&path, bytes::Bytes::from(bytes.to_vec()).into())
-        .await
-        .unwrap();
+        .await?;
 
     // Create scheduler and open file
     let scheduler = ScanScheduler::new(
@@ -88,8 +117,7 @@ pub async fn read_bytes(bytes: &[u8]) -> Vec<RecordBatch> {
     );
     let file_scheduler = scheduler
         .open_file(&path, &CachedFileSize::unknown())
-        .await
-        .unwrap();
+        .await?;
 
     // Open the file reader
     let cache = LanceCache::no_cache();
@@ -100,85 +128,309 @@ pub async fn read_bytes(bytes: &[u8]) -> Vec<RecordBatch> {
         &cache,
         FileReaderOptions::default(),
     )
-    .await
-    .unwrap();
+    .await?;
 
-    let num_rows = reader.num_rows();
+    Ok((reader, scheduler))
+}
+
+fn column_projection(reader: &FileReader, columns: &[String]) -> Result<ReaderProjection> {
+    let names: Vec<&str> = columns.iter().map(String::as_str).collect();
+    Ok(ReaderProjection::from_column_names(
+        reader.metadata().version(),
+        reader.schema(),
+        &names,
+    )?)
+}
+
+fn read_stats(scheduler: &ScanScheduler) -> ReadStats {
+    let stats = scheduler.stats();
+    ReadStats {
+        bytes_read: stats.bytes_read,
+        requests: stats.iops,
+    }
+}
+
+pub async fn read_bytes(bytes: &[u8]) -> Result<Vec<RecordBatch>> {
+    let (reader, _) = open_reader(bytes).await?;
     let projection =
         ReaderProjection::from_whole_schema(reader.schema(), reader.metadata().version());
+    scan(&reader, projection).await
+}
+
+pub async fn read_bytes_projected(
+    bytes: &[u8],
+    columns: &[String],
+) -> Result<(Vec<RecordBatch>, ReadStats)> {
+    let (reader, scheduler) = open_reader(bytes).await?;
+    let projection = column_projection(&reader, columns)?;
+    let batches = scan(&reader, projection).await?;
+    Ok((batches, read_stats(&scheduler)))
+}
+
+async fn scan(reader: &FileReader, projection: ReaderProjection) -> Result<Vec<RecordBatch>> {
+    let num_rows = reader.num_rows();
 
     // Read all data
-    let stream = reader
-        .read_tasks(
-            ReadBatchParams::Range(0..num_rows as usize),
-            1024,
-            Some(projection),
-            FilterExpression::no_filter(),
-        )
-        .unwrap();
+    let stream = reader.read_tasks(
+        ReadBatchParams::Range(0..num_rows as usize),
+        1024,
+        Some(projection),
+        FilterExpression::no_filter(),
+    )?;
 
     let mut batches = Vec::new();
     futures::pin_mut!(stream);
     while let Some(batch_task) = stream.next().await {
-        let batch = batch_task.task.await.unwrap();
+        let batch = batch_task.task.await?;
         batches.push(batch);
     }
-    batches
+    Ok(batches)
 }
 
-pub async fn take_rows_from_bytes(bytes: &[u8], indices: &[usize]) -> RecordBatch {
-    // Since we only read one row, optimize for this case
-    assert_eq!(indices.len(), 1, "Expected exactly one index");
-    
-    // Use memory object store
-    let object_store = Arc::new(ObjectStore::memory());
-    let path = Path::from("data.lance");
+pub async fn take_rows_from_bytes(bytes: &[u8], indices: &[usize]) -> Result<RecordBatch> {
+    let (reader, _) = open_reader(bytes).await?;
+    let projection =
+        ReaderProjection::from_whole_schema(reader.schema(), reader.metadata().version());
+    take(&reader, indices, projection).await
+}
 
-    // Write bytes to memory store
-    object_store
-        .inner
-        .put(&path, bytes::Bytes::from(bytes.to_vec()).into())
-        .await
-        .unwrap();
+// Take `indices` reading only `columns`.
+pub async fn take_rows_projected(
+    bytes: &[u8],
+    indices: &[usize],
+    columns: &[String],
+) -> Result<(RecordBatch, ReadStats)> {
+    let (reader, scheduler) = open_reader(bytes).await?;
+    let projection = column_projection(&reader, columns)?;
+    let batch = take(&reader, indices, projection).await?;
+    Ok((batch, read_stats(&scheduler)))
+}
 
-    // Create scheduler and open file
-    let scheduler = ScanScheduler::new(
-        object_store.clone(),
-        SchedulerConfig::max_bandwidth(&object_store),
-    );
-    let file_scheduler = scheduler
-        .open_file(&path, &CachedFileSize::unknown())
-        .await
-        .unwrap();
+// Take `indices` in any order, repeats included. Indices past the end of the
+// file are an error.
+async fn take(
+    reader: &FileReader,
+    indices: &[usize],
+    projection: ReaderProjection,
+) -> Result<RecordBatch> {
+    let plan = TakePlan::new(indices, reader.num_rows() as usize)?;
+    let schema = Arc::new(Schema::from(projection.schema.as_ref()));
 
-    // Open the file reader
-    let cache = LanceCache::no_cache();
-    let reader = FileReader::try_open(
-        file_scheduler,
-        None,
-        Arc::<DecoderPlugins>::default(),
-        &cache,
-        FileReaderOptions::default(),
-    )
-    .await
-    .unwrap();
+    let indices: Vec<u32> = plan.rows.iter().map(|&i| i as u32).collect();
+    if indices.is_empty() {
+        return Ok(RecordBatch::new_empty(schema));
+    }
 
-    let projection =
-        ReaderProjection::from_whole_schema(reader.schema(), reader.metadata().version());
+    let batch_size = indices.len() as u32;
+    let stream = reader.read_tasks(
+        ReadBatchParams::Indices(arrow_array::UInt32Array::from(indices)),
+        batch_size,
+        Some(projection),
+        FilterExpression::no_filter(),
+    )?;
 
-    // Take single row using indices
-    let indices_array = arrow_array::UInt32Array::from(vec![indices[0] as u32]);
-    let stream = reader
-        .read_tasks(
-            ReadBatchParams::Indices(indices_array),
-            1, // batch size of 1 for single row
-            Some(projection),
-            FilterExpression::no_filter(),
-        )
-        .unwrap();
-
-    // Read the single batch
+    let mut batches = Vec::new();
     futures::pin_mut!(stream);
-    let batch_task = stream.next().await.unwrap();
-    batch_task.task.await.unwrap()
+    while let Some(batch_task) = stream.next().await {
+        batches.push(batch_task.task.await?);
+    }
+    plan.restore(arrow_select::concat::concat_batches(&schema, &batches)?)
+}
+
+pub async fn summarize(bytes: &[u8]) -> Result<FileSummary> {
+    let (reader, _) = open_reader(bytes).await?;
+    let metadata = reader.metadata();
+    Ok(FileSummary {
+        file_bytes: bytes.len(),
+        num_rows: metadata.num_rows,
+        num_columns: metadata.column_metadatas.len(),
+        num_chunks: metadata
+            .column_metadatas
+            .iter()
+            .map(|column| column.pages.len())
+            .sum(),
+    })
+}
+
+pub async fn column_sizes(bytes: &[u8]) -> Result<ColumnSizes> {
+    let (reader, _) = open_reader(bytes).await?;
+    let metadata = reader.metadata();
+    let names = column_names(&reader, metadata.column_metadatas.len());
+
+    let columns = metadata
+        .column_metadatas
+        .iter()
+        .zip(names)
+        .map(|(column, name)| {
+            // Page buffers plus any buffers shared by the whole column
+            let pages: u64 = column
+                .pages
+                .iter()
+                .flat_map(|page| page.buffer_sizes.iter())
+                .sum();
+            let shared: u64 = column.buffer_sizes.iter().sum();
+            ColumnSize {
+                column: name,
+                compressed_bytes: pages + shared,
+                uncompressed_bytes: None,
+            }
+        })
+        .collect();
+    Ok(ColumnSizes {
+        file_bytes: bytes.len() as u64,
+        columns,
+    })
+}
+
+// The schema is the first global buffer; the footer holds the offset tables
+// for column metadata and global buffers plus the fixed-size trailer
+pub async fn layout(bytes: &[u8]) -> Result<FileLayout> {
+    let (reader, _) = open_reader(bytes).await?;
+    let metadata = reader.metadata();
+    let schema = metadata
+        .file_buffers
+        .first()
+        .map_or(0, |buffer| buffer.size);
+    FileLayout::new(
+        bytes.len() as u64,
+        vec![
+            ("data pages", metadata.num_data_bytes),
+            ("column metadata", metadata.num_column_metadata_bytes),
+            ("schema", schema),
+            (
+                "other global buffers",
+                metadata.num_global_buffer_bytes.saturating_sub(schema),
+            ),
+            ("footer", metadata.num_footer_bytes),
+        ],
+    )
+}
+
+// Name each column after the field it stores. Columns follow the schema's
+// fields in pre-order, every field up to 2.0 and only leaves from 2.1, so
+// each one is matched to its field by id and named with the field's path.
+fn column_names(reader: &FileReader, num_columns: usize) -> Vec<String> {
+    let schema = reader.schema();
+    let structural = reader.metadata().version() >= LanceFileVersion::V2_1;
+    let mut paths = HashMap::new();
+    field_paths(&schema.fields, "", &mut paths);
+
+    let mut names: Vec<String> = schema
+        .fields_pre_order()
+        .filter(|field| !structural || field.children.is_empty())
+        .map(|field| paths[&field.id].clone())
+        .collect();
+    if names.len() != num_columns {
+        // A layout this doesn't know, e.g. packed structs
+        names = (0..num_columns).map(|i| format!("column{}", i)).collect();
+    }
+    names
+}
+
+// Dotted path of every field, by field id
+fn field_paths(fields: &[LanceField], parent: &str, paths: &mut HashMap<i32, String>) {
+    for field in fields {
+        let path = if parent.is_empty() {
+            field.name.clone()
+        } else {
+            format!("{}.{}", parent, field.name)
+        };
+        field_paths(&field.children, &path, paths);
+        paths.insert(field.id, path);
+    }
+}
+
+// What the writer actually chose per page, which may differ from the
+// compression requested through the field metadata
+pub async fn encodings(bytes: &[u8]) -> Result<Vec<ChunkEncoding>> {
+    let (reader, _) = open_reader(bytes).await?;
+    let metadata = reader.metadata();
+    let names = column_names(&reader, metadata.column_infos.len());
+
+    let mut chunks = Vec::new();
+    for (column, name) in metadata.column_infos.iter().zip(names) {
+        for (chunk, page) in column.page_infos.iter().enumerate() {
+            let (layout, encodings) = page_encodings(&page.encoding);
+            chunks.push(ChunkEncoding {
+                column: name.clone(),
+                chunk,
+                num_values: page.num_rows,
+                layout,
+                encodings,
+                buffer_sizes: page
+                    .buffer_offsets_and_sizes
+                    .iter()
+                    .map(|(_, size)| *size)
+                    .collect(),
+                compression: None,
+                detail: format!("{:#?}", page.encoding),
+            });
+        }
+    }
+    Ok(chunks)
+}
+
+// Layout of a page and the encodings of its values, outermost first. Pages
+// from 2.1 on name their layout; 2.0 pages have none and only their top
+// encoding is named, the full tree is kept in `ChunkEncoding::detail`.
+fn page_encodings(encoding: &PageEncoding) -> (String, Vec<String>) {
+    use pb::page_layout::Layout;
+    let layout = match encoding {
+        PageEncoding::Legacy(array) => {
+            return ("legacy".to_string(), array_encodings(Some(array)));
+        }
+        PageEncoding::Structural(layout) => layout,
+    };
+    match &layout.layout {
+        Some(Layout::MiniBlockLayout(mini_block)) => {
+            let mut encodings = array_encodings(mini_block.value_compression.as_ref());
+            if mini_block.dictionary.is_some() {
+                encodings.insert(0, "Dictionary".to_string());
+            }
+            ("MiniBlockLayout".to_string(), encodings)
+        }
+        Some(Layout::FullZipLayout(full_zip)) => (
+            "FullZipLayout".to_string(),
+            array_encodings(full_zip.value_compression.as_ref()),
+        ),
+        Some(Layout::AllNullLayout(_)) => ("AllNullLayout".to_string(), Vec::new()),
+        _ => ("unknown".to_string(), Vec::new()),
+    }
+}
+
+// General mini-blocks wrap another encoding, which is named after them
+fn array_encodings(encoding: Option<&pb::ArrayEncoding>) -> Vec<String> {
+    use pb::array_encoding::ArrayEncoding as Encoding;
+    let Some(encoding) = encoding.and_then(|e| e.array_encoding.as_ref()) else {
+        return Vec::new();
+    };
+    let name = match encoding {
+        Encoding::GeneralMiniBlock(general) => {
+            let mut names = vec!["GeneralMiniBlock".to_string()];
+            names.extend(array_encodings(general.inner.as_deref()));
+            return names;
+        }
+        Encoding::Flat(_) => "Flat",
+        Encoding::Nullable(_) => "Nullable",
+        Encoding::FixedSizeList(_) => "FixedSizeList",
+        Encoding::List(_) => "List",
+        Encoding::Struct(_) => "Struct",
+        Encoding::Binary(_) => "Binary",
+        Encoding::Dictionary(_) => "Dictionary",
+        Encoding::Fsst(_) => "Fsst",
+        Encoding::PackedStruct(_) => "PackedStruct",
+        Encoding::Bitpacked(_) => "Bitpacked",
+        Encoding::FixedSizeBinary(_) => "FixedSizeBinary",
+        Encoding::BitpackedForNonNeg(_) => "BitpackedForNonNeg",
+        Encoding::Constant(_) => "Constant",
+        Encoding::InlineBitpacking(_) => "InlineBitpacking",
+        Encoding::OutOfLineBitpacking(_) => "OutOfLineBitpacking",
+        Encoding::Variable(_) => "Variable",
+        Encoding::Block(_) => "Block",
+        Encoding::Rle(_) => "Rle",
+        // Encodings added to the format since these were listed
+        #[allow(unreachable_patterns)]
+        _ => "unknown",
+    };
+    vec![name.to_string()]
 }
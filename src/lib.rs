@@ -1,3 +1,23 @@
+pub mod advise;
+pub mod baseline;
+pub mod charts;
 pub mod data;
+pub mod dataset;
+pub mod error;
+pub mod format;
+pub mod inspect;
 pub mod lance;
+pub mod mutate;
+pub mod ordering;
 pub mod parquet;
+pub mod projection;
+pub mod provenance;
+pub mod results;
+pub mod runner;
+pub mod runs;
+pub mod size;
+pub mod split;
+pub mod stats;
+pub mod sweep;
+pub mod take;
+pub mod verify;
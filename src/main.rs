@@ -1,103 +1,511 @@
-use arrow_array::RecordBatch;
-use lance_rle_benchmark::{data::{generate_nested_record_batch, generate_flat_record_batch}, lance, parquet};
-use tokio::runtime::Runtime;
-
-struct CompressionRow {
-    size: usize,
-    lance: String,
-    lance_rle: String,
-    parquet: String,
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use lance_rle_benchmark::advise::Objective;
+use lance_rle_benchmark::baseline;
+use lance_rle_benchmark::charts;
+use lance_rle_benchmark::data::{SchemaKind, DEFAULT_SEED};
+use lance_rle_benchmark::dataset::DatasetOptions;
+use lance_rle_benchmark::error::Result;
+use lance_rle_benchmark::format::FormatConfig;
+use lance_rle_benchmark::ordering::RowOrder;
+use lance_rle_benchmark::projection::ColumnSelection;
+use lance_rle_benchmark::provenance::Provenance;
+use lance_rle_benchmark::results::{read_records, Record, ResultFormat};
+use lance_rle_benchmark::runner::{ReadOp, RunOptions, Runner};
+use lance_rle_benchmark::sweep::{Sweep, SweepConfig};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(about = "Compare Lance RLE against Lance bitpacking and Parquet")]
+struct Cli {
+    #[command(flatten)]
+    matrix: MatrixArgs,
+
+    #[command(flatten)]
+    baseline: BaselineArgs,
+
+    // Defaults to `matrix`
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Args)]
+struct MatrixArgs {
+    /// Format configurations, e.g. lance-v2.1-rle, lance-bitpacking, parquet-zstd
+    #[arg(
+        long = "format",
+        short = 'f',
+        global = true,
+        value_delimiter = ',',
+        default_values = ["lance-bitpacking", "lance-rle", "parquet"]
+    )]
+    formats: Vec<FormatConfig>,
+
+    /// Row counts to generate
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        default_values_t = [1_000, 10_000, 100_000]
+    )]
+    rows: Vec<usize>,
+
+    /// Generated schemas: nested, flat or nullable
+    #[arg(
+        long = "schema",
+        global = true,
+        value_delimiter = ',',
+        default_values = ["nested", "flat"]
+    )]
+    schemas: Vec<SchemaKind>,
+
+    /// Seed for the data generator
+    #[arg(long, global = true, default_value_t = DEFAULT_SEED)]
+    seed: u64,
+
+    /// Measured runs behind every timing; below 5 there is no confidence
+    /// interval or noise flag
+    #[arg(long, global = true, default_value_t = 10)]
+    repetitions: usize,
+
+    /// Unmeasured runs before each timing
+    #[arg(long, global = true, default_value_t = 1)]
+    warmup: usize,
+
+    /// Write the report here instead of stdout
+    #[arg(long, short = 'o', global = true)]
+    output: Option<PathBuf>,
+
+    /// Also write raw measurements here, for dashboards and notebooks
+    #[arg(long, global = true)]
+    results: Option<PathBuf>,
+
+    /// json, csv or parquet; guessed from the --results extension by default
+    #[arg(long, global = true, requires = "results")]
+    results_format: Option<ResultFormat>,
+
+    /// Render SVG charts and an index.html into this directory
+    #[arg(long, global = true)]
+    charts: Option<PathBuf>,
+
+    /// Decode every written file and abort if it differs from the source
+    #[arg(long, global = true)]
+    verify: bool,
+}
+
+#[derive(Args)]
+struct BaselineArgs {
+    /// Save this run's results as the named baseline
+    #[arg(long, global = true)]
+    save_baseline: Option<String>,
+
+    /// Compare this run against the named baseline
+    #[arg(long, global = true)]
+    baseline: Option<String>,
+
+    /// Where named baselines are stored
+    #[arg(long, global = true, default_value = "baselines")]
+    baseline_dir: PathBuf,
+
+    /// Percentage increase in size or latency that counts as a regression
+    #[arg(long, global = true, default_value_t = 5.0)]
+    threshold: f64,
+}
+
+// What a run found beyond the report itself
+struct Outcome {
+    failures: usize,
+    regressions: usize,
 }
 
-fn test_schema(schema_name: &str, generate_fn: fn(usize) -> RecordBatch) {
-    println!("\n### {}", schema_name);
-    println!("Data pattern: 40% zeros, 40% common values, 20% random values");
-
-    let rt = Runtime::new().unwrap();
-    let mut rows = vec![];
-
-    // Test with different row counts
-    for num_rows in [1_000, 10_000, 100_000] {
-        let batch = generate_fn(num_rows);
-        // uuid: 8 bytes + features: 8 bytes * 3827
-        let original_size = num_rows * (8 + 8 * 3827);
-
-        // Lance default (bitpacking)
-        let lance_bytes = rt.block_on(lance::write_bytes(batch.clone(), false, false));
-        let lance_size = lance_bytes.len();
-
-        // Lance with RLE
-        let lance_rle_bytes = rt.block_on(lance::write_bytes(batch.clone(), false, true));
-        let lance_rle_size = lance_rle_bytes.len();
-
-        // Parquet
-        let parquet_bytes = parquet::write_bytes(batch);
-        let parquet_size = parquet_bytes.len();
-
-        let lance_ratio_val = original_size as f64 / lance_size as f64;
-        let lance_rle_ratio_val = original_size as f64 / lance_rle_size as f64;
-        let parquet_ratio_val = original_size as f64 / parquet_size as f64;
-        
-        // Find the best compression ratio
-        let best_ratio = lance_ratio_val.max(lance_rle_ratio_val).max(parquet_ratio_val);
-        
-        // Format size and ratio combined with best one marked
-        let lance_str = if (lance_ratio_val - best_ratio).abs() < 0.0001 {
-            format!("{} (**{:.2}x**)", lance_size, lance_ratio_val)
-        } else {
-            format!("{} ({:.2}x)", lance_size, lance_ratio_val)
-        };
-        
-        let lance_rle_str = if (lance_rle_ratio_val - best_ratio).abs() < 0.0001 {
-            format!("{} (**{:.2}x**)", lance_rle_size, lance_rle_ratio_val)
-        } else {
-            format!("{} ({:.2}x)", lance_rle_size, lance_rle_ratio_val)
-        };
-        
-        let parquet_str = if (parquet_ratio_val - best_ratio).abs() < 0.0001 {
-            format!("{} (**{:.2}x**)", parquet_size, parquet_ratio_val)
-        } else {
-            format!("{} ({:.2}x)", parquet_size, parquet_ratio_val)
-        };
-        
-        rows.push(CompressionRow {
-            size: num_rows,
-            lance: lance_str,
-            lance_rle: lance_rle_str,
-            parquet: parquet_str,
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Compressed size of every configuration
+    Compress,
+    /// Size, write and scan throughput and take latency in one table
+    Summary,
+    /// Full or projected scan latency and bytes read
+    Scan {
+        /// all, first-K, random-K[:SEED] or featureN
+        #[arg(long, value_delimiter = ',', default_value = "all")]
+        columns: Vec<ColumnSelection>,
+    },
+    /// Take latency and bytes read
+    Take {
+        /// Row indices to take, in any order; defaults to the middle row
+        #[arg(long, value_delimiter = ',')]
+        indices: Vec<usize>,
+        /// all, first-K, random-K[:SEED] or featureN
+        #[arg(long, value_delimiter = ',', default_value = "all")]
+        columns: Vec<ColumnSelection>,
+    },
+    /// Compression, the size and speed summary, and projected scans and takes
+    Matrix,
+    /// File shape as reported by each format's metadata
+    Inspect,
+    /// Compressed bytes per column, with the largest columns listed
+    Columns {
+        /// How many of the largest columns to list
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Run statistics per column and estimated sizes under RLE, bitpacking,
+    /// dictionary and plain encodings, against the actual files
+    Runs {
+        /// Columns to list one by one: all, first-K, random-K[:SEED] or featureN
+        #[arg(long, value_delimiter = ',', default_value = "first-10")]
+        columns: Vec<ColumnSelection>,
+    },
+    /// Lance datasets: write, bytes on disk, open, scan, take and take_rows
+    Dataset {
+        /// Rows per data file; defaults to Lance's own default
+        #[arg(long)]
+        max_rows_per_file: Option<usize>,
+        /// Rows per row group; defaults to Lance's own default
+        #[arg(long)]
+        max_rows_per_group: Option<usize>,
+        /// all, first-K, random-K[:SEED] or featureN
+        #[arg(long, value_delimiter = ',', default_values = ["all", "first-10"])]
+        columns: Vec<ColumnSelection>,
+        /// Row offsets to take; defaults to the middle row
+        #[arg(long, value_delimiter = ',')]
+        indices: Vec<usize>,
+    },
+    /// Append, delete and update a table, measuring write amplification and
+    /// reads after each change
+    Mutate {
+        /// Rows to append
+        #[arg(long, default_value_t = 1_000)]
+        append_rows: usize,
+        /// Delete and update one row in this many, at least 2
+        #[arg(long, default_value_t = 10)]
+        every: i64,
+        /// Feature columns to update, `uuid` never included: all, first-K,
+        /// random-K[:SEED] or featureN
+        #[arg(long, default_value = "first-10")]
+        update_columns: ColumnSelection,
+    },
+    /// Build Lance datasets from many small appends, then compact them,
+    /// measuring reads and size before and after
+    Compact {
+        /// Rows per append, each one a new fragment
+        #[arg(long, default_value_t = 1_000)]
+        append_rows: usize,
+        /// Rows to take, spread evenly over the table
+        #[arg(long, default_value_t = 100)]
+        takes: usize,
+    },
+    /// Add feature columns to a written table, Lance in place and Parquet by
+    /// rewriting the file, then read old and new columns
+    Evolve {
+        /// Feature columns to add
+        #[arg(long, default_value_t = 10)]
+        add: usize,
+        /// Rows to take, spread evenly over the table
+        #[arg(long, default_value_t = 100)]
+        takes: usize,
+    },
+    /// Scan and take cost as the table is split into more fragments, files
+    /// or row groups
+    Scaling {
+        /// Numbers of pieces to split the table into
+        #[arg(long, value_delimiter = ',', default_values_t = [1, 4, 16, 64])]
+        pieces: Vec<usize>,
+        /// all, first-K, random-K[:SEED] or featureN
+        #[arg(long, value_delimiter = ',', default_values = ["all", "first-10"])]
+        columns: Vec<ColumnSelection>,
+        /// Rows to take, spread evenly over the table
+        #[arg(long, default_value_t = 100)]
+        takes: usize,
+    },
+    /// Size, scan and take cost with the rows reordered before writing
+    Reorder {
+        /// original, sort:COL[+COL], cluster or shuffle[:SEED]
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_values = ["original", "cluster", "shuffle"]
+        )]
+        orders: Vec<RowOrder>,
+        /// Original row indices to take; defaults to ten evenly spread rows
+        #[arg(long, value_delimiter = ',')]
+        indices: Vec<usize>,
+    },
+    /// Recommend a Lance compression per column from a sample of the data
+    Advise {
+        /// Rows in the sample each candidate is tried on
+        #[arg(long, default_value_t = 10_000)]
+        sample_rows: usize,
+        /// What to minimize
+        #[arg(long, value_enum, default_value_t = Minimize::Size)]
+        minimize: Minimize,
+        /// Limit on the other measure, summed over columns: scan ms when
+        /// minimizing size, bytes when minimizing latency
+        #[arg(long)]
+        budget: Option<f64>,
+        /// Write the per-column field metadata as JSON, one file per schema and
+        /// lance version
+        #[arg(long)]
+        metadata_out: Option<PathBuf>,
+        /// Write the advised schema as an Arrow IPC file, one per schema and
+        /// lance version
+        #[arg(long)]
+        schema_out: Option<PathBuf>,
+    },
+    /// Data, metadata and footer bytes of each file, and their share of it
+    Layout,
+    /// Encodings each format actually wrote, per column and page or chunk
+    Encodings {
+        /// Columns to list chunk by chunk: all, first-K, random-K[:SEED] or featureN
+        #[arg(long, value_delimiter = ',', default_value = "first-10")]
+        columns: Vec<ColumnSelection>,
+        /// Also dump the full Lance encoding tree or Parquet page encoding stats
+        #[arg(long)]
+        tree: bool,
+    },
+    /// Take the measurements of an earlier run from its results file instead
+    /// of running, to chart it with --charts, save it with --save-baseline or
+    /// check it with --baseline
+    Results {
+        /// Results file; the format is guessed from the extension
+        path: PathBuf,
+    },
+    /// Run every cell of an experiment file, skipping cells already in its
+    /// results file. The matrix options above don't apply.
+    Run {
+        /// TOML experiment file, see src/sweep.rs for the format
+        config: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Minimize {
+    Size,
+    Latency,
+}
+
+fn run<W: Write>(
+    command: Command,
+    options: RunOptions,
+    charts: Option<&Path>,
+    baselines: &BaselineArgs,
+    out: W,
+) -> Result<Outcome> {
+    if let Command::Run { config: path } = command {
+        let config = SweepConfig::load(&path)?;
+        let results = config.results_path(&path);
+        let provenance = Provenance::capture(config.seed, fs::read_to_string(&path)?);
+        let failures = Sweep::new(config, results, provenance, out)?.run()?;
+        return Ok(Outcome {
+            failures,
+            regressions: 0,
         });
     }
 
-    // Print markdown table header
-    println!("\n| Rows | Lance (bitpacking) | Lance (RLE) | Parquet |");
-    println!("|------|--------------------|-------------|---------|");
-    
-    // Print each row
-    for row in rows {
-        println!(
-            "| {} | {} | {} | {} |",
-            row.size,
-            row.lance,
-            row.lance_rle,
-            row.parquet
-        );
+    // Load up front so a missing baseline fails before the run, not after
+    let baseline = match &baselines.baseline {
+        Some(name) => Some((name, baseline::load(&baselines.baseline_dir, name)?)),
+        None => None,
+    };
+
+    if let Command::Results { path } = &command {
+        let format = ResultFormat::from_path(path);
+        return saved_results(path, format, charts, baseline, baselines, out);
+    }
+
+    let provenance = Provenance::capture(
+        options.seed,
+        format!("{:#?}\n\ncommand: {:?}", options, command),
+    );
+    let mut runner = Runner::new(options, provenance, out)?;
+    match command {
+        Command::Compress => runner.compress()?,
+        Command::Summary => runner.summary()?,
+        Command::Scan { columns } => runner.reads(&[ReadOp::Scan], &columns)?,
+        Command::Take { indices, columns } => runner.reads(&[ReadOp::Take(&indices)], &columns)?,
+        Command::Matrix => {
+            runner.compress()?;
+            runner.summary()?;
+            runner.reads(
+                &[ReadOp::Scan, ReadOp::Take(&[])],
+                &[
+                    ColumnSelection::All,
+                    ColumnSelection::Single(0),
+                    ColumnSelection::FirstK(10),
+                    ColumnSelection::FirstK(50),
+                    ColumnSelection::RandomK { k: 10, seed: 42 },
+                    ColumnSelection::RandomK { k: 50, seed: 42 },
+                ],
+            )?;
+        }
+        Command::Inspect => runner.inspect()?,
+        Command::Columns { top } => runner.columns(top)?,
+        Command::Runs { columns } => runner.runs(&columns)?,
+        Command::Dataset {
+            max_rows_per_file,
+            max_rows_per_group,
+            columns,
+            indices,
+        } => {
+            let defaults = DatasetOptions::default();
+            let options = DatasetOptions {
+                max_rows_per_file: max_rows_per_file.unwrap_or(defaults.max_rows_per_file),
+                max_rows_per_group: max_rows_per_group.unwrap_or(defaults.max_rows_per_group),
+            };
+            runner.datasets(options, &columns, &indices)?
+        }
+        Command::Mutate {
+            append_rows,
+            every,
+            update_columns,
+        } => runner.mutations(append_rows, every, &update_columns)?,
+        Command::Compact { append_rows, takes } => runner.compaction(append_rows, takes)?,
+        Command::Evolve { add, takes } => runner.schema_evolution(add, takes)?,
+        Command::Scaling {
+            pieces,
+            columns,
+            takes,
+        } => runner.scaling(&pieces, &columns, takes)?,
+        Command::Reorder { orders, indices } => runner.orderings(&orders, &indices)?,
+        Command::Advise {
+            sample_rows,
+            minimize,
+            budget,
+            metadata_out,
+            schema_out,
+        } => {
+            let objective = match minimize {
+                Minimize::Size => Objective::MinSize {
+                    max_scan_ms: budget,
+                },
+                Minimize::Latency => Objective::MinLatency {
+                    max_bytes: budget.map(|bytes| bytes as u64),
+                },
+            };
+            runner.advise(
+                sample_rows,
+                objective,
+                metadata_out.as_deref(),
+                schema_out.as_deref(),
+            )?
+        }
+        Command::Layout => runner.layout()?,
+        Command::Encodings { columns, tree } => runner.encodings(&columns, tree)?,
+        Command::Run { .. } | Command::Results { .. } => {
+            unreachable!("handled without a runner")
+        }
+    }
+
+    if let Some(dir) = charts {
+        let files = charts::render(runner.records(), dir)?;
+        eprintln!("wrote {} chart file(s) to {}", files.len(), dir.display());
+    }
+    let mut regressions = 0;
+    if let Some((name, records)) = &baseline {
+        regressions = runner.compare(name, records, baselines.threshold)?;
+    }
+    if let Some(name) = &baselines.save_baseline {
+        let path = baseline::save(
+            runner.records(),
+            runner.provenance(),
+            &baselines.baseline_dir,
+            name,
+        )?;
+        eprintln!("saved baseline `{}` to {}", name, path.display());
+    }
+    Ok(Outcome {
+        failures: runner.finish()?.len(),
+        regressions,
+    })
+}
+
+// Chart, compare and save the records of an earlier run, as `run` does for a
+// new one
+fn saved_results<W: Write>(
+    path: &Path,
+    format: ResultFormat,
+    charts: Option<&Path>,
+    baseline: Option<(&String, Vec<Record>)>,
+    baselines: &BaselineArgs,
+    mut out: W,
+) -> Result<Outcome> {
+    let records = read_records(path, format)?;
+    if let Some(dir) = charts {
+        let files = charts::render(&records, dir)?;
+        eprintln!("wrote {} chart file(s) to {}", files.len(), dir.display());
+    }
+    let mut regressions = 0;
+    if let Some((name, old)) = baseline {
+        let deltas = baseline::compare(&old, &records, baselines.threshold);
+        baseline::write_deltas(&mut out, name, &deltas)?;
+        regressions = deltas.iter().filter(|d| d.regression).count();
+    }
+    out.flush()?;
+    if let Some(name) = &baselines.save_baseline {
+        let saved = baseline::save_file(path, format, &baselines.baseline_dir, name)?;
+        eprintln!("saved baseline `{}` to {}", name, saved.display());
     }
+    Ok(Outcome {
+        failures: records.iter().filter(|r| r.error.is_some()).count(),
+        regressions,
+    })
 }
 
 fn main() {
-    println!("\n=== RLE Compression Benchmark ===");
-    
-    // Test nested schema
-    test_schema(
-        "Nested Schema: uuid (int64) + features (struct with 3827 double fields)",
-        generate_nested_record_batch
-    );
-    
-    // Test flat schema
-    test_schema(
-        "Flat Schema: uuid (int64) + 3827 double columns",
-        generate_flat_record_batch
-    );
-    
-    println!("\n**Note**: Best compression ratio for each test is marked with **bold**.");
+    let cli = Cli::parse();
+    let matrix = cli.matrix;
+    let options = RunOptions {
+        configs: matrix.formats,
+        schemas: matrix.schemas,
+        rows: matrix.rows,
+        seed: matrix.seed,
+        repetitions: matrix.repetitions,
+        warmup: matrix.warmup,
+        verify: matrix.verify,
+        results: matrix.results.map(|path| {
+            let format = matrix
+                .results_format
+                .unwrap_or_else(|| ResultFormat::from_path(&path));
+            (path, format)
+        }),
+    };
+    let command = cli.command.unwrap_or(Command::Matrix);
+    let charts = matrix.charts.as_deref();
+
+    let result = match &matrix.output {
+        Some(path) => File::create(path).map_err(Into::into).and_then(|file| {
+            run(
+                command,
+                options,
+                charts,
+                &cli.baseline,
+                BufWriter::new(file),
+            )
+        }),
+        None => run(command, options, charts, &cli.baseline, io::stdout().lock()),
+    };
+
+    // Regressions exit with 2, failed configurations with 3
+    match result {
+        Ok(outcome) => {
+            if outcome.failures > 0 {
+                eprintln!(
+                    "{} configuration(s) failed, see the report",
+                    outcome.failures
+                );
+            }
+            if outcome.regressions > 0 {
+                eprintln!("{} regression(s) against the baseline", outcome.regressions);
+                std::process::exit(2);
+            }
+            if outcome.failures > 0 {
+                std::process::exit(3);
+            }
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            std::process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,314 @@
+use crate::dataset::{self, disk_usage, DatasetDir, DatasetOptions};
+use crate::error::{Error, Result};
+use crate::format::FormatConfig;
+use crate::parquet;
+use crate::projection::leaf_arrays;
+use crate::size::batch_size;
+use ::lance::dataset::{Dataset, UpdateBuilder, WriteMode};
+use arrow::array::AsArray;
+use arrow::compute::kernels::numeric::add;
+use arrow::compute::kernels::zip::zip;
+use arrow::compute::{filter, filter_record_batch};
+use arrow::datatypes::Int64Type;
+use arrow_array::{Array, ArrayRef, BooleanArray, Float64Array, RecordBatch, StructArray};
+use arrow_schema::{DataType, Schema};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// A change to a table. Rows are picked by `uuid`, so Lance can be given the
+// same predicate as SQL that is applied to Arrow data for Parquet.
+#[derive(Debug, Clone)]
+pub enum Mutation {
+    Append(RecordBatch),
+    // Delete rows where `uuid % every = 0`
+    Delete { every: i64 },
+    // Add one to the float64 `columns` where `uuid % every = 1`
+    Update { every: i64, columns: Vec<String> },
+    // New top-level columns, a row for every table row in order
+    AddColumns(RecordBatch),
+}
+
+impl Mutation {
+    pub fn label(&self) -> String {
+        match self {
+            Mutation::Append(batch) => format!("append {} rows", batch.num_rows()),
+            Mutation::Delete { every } => format!("delete 1/{}", every),
+            Mutation::Update { every, columns } => {
+                format!("update {} columns of 1/{}", columns.len(), every)
+            }
+            Mutation::AddColumns(columns) => format!("add {} columns", columns.num_columns()),
+        }
+    }
+
+    fn predicate(&self) -> Option<String> {
+        match self {
+            Mutation::Append(_) | Mutation::AddColumns(_) => None,
+            Mutation::Delete { every } => Some(format!("uuid % {} = 0", every)),
+            Mutation::Update { every, .. } => Some(format!("uuid % {} = 1", every)),
+        }
+    }
+
+    // Rows of `table` the predicate matches
+    fn matches(&self, table: &RecordBatch) -> Result<BooleanArray> {
+        let (every, remainder) = match self {
+            Mutation::Append(_) | Mutation::AddColumns(_) => {
+                return Ok(BooleanArray::from(vec![false; table.num_rows()]))
+            }
+            Mutation::Delete { every } => (*every, 0),
+            Mutation::Update { every, .. } => (*every, 1),
+        };
+        let uuid = table
+            .column_by_name("uuid")
+            .and_then(|column| column.as_primitive_opt::<Int64Type>())
+            .ok_or_else(|| {
+                Error::InvalidInput("mutations need an int64 uuid column".to_string())
+            })?;
+        Ok(uuid
+            .iter()
+            .map(|uuid| uuid.map(|uuid| uuid % every == remainder))
+            .collect())
+    }
+
+    // Logical bytes the change touches: appended rows, deleted rows, or the
+    // updated values
+    pub fn changed_bytes(&self, table: &RecordBatch) -> Result<u64> {
+        let matches = self.matches(table)?;
+        Ok(match self {
+            Mutation::Append(batch) | Mutation::AddColumns(batch) => batch_size(batch)?.logical,
+            Mutation::Delete { .. } => batch_size(&filter_record_batch(table, &matches)?)?.logical,
+            Mutation::Update { columns, .. } => {
+                let mut bytes = 0;
+                for (name, _, array) in leaf_arrays(table) {
+                    if columns.contains(&name) {
+                        let updated = filter(&array, &matches)?;
+                        bytes += updated.to_data().get_slice_memory_size()? as u64;
+                    }
+                }
+                bytes
+            }
+        })
+    }
+
+    // The same change made to in-memory Arrow data
+    pub fn apply(&self, table: &RecordBatch) -> Result<RecordBatch> {
+        let matches = self.matches(table)?;
+        match self {
+            Mutation::Append(batch) => Ok(arrow_select::concat::concat_batches(
+                &table.schema(),
+                [table, batch],
+            )?),
+            Mutation::Delete { .. } => {
+                let keep = arrow::compute::not(&matches)?;
+                Ok(filter_record_batch(table, &keep)?)
+            }
+            Mutation::Update { columns, .. } => {
+                let mut updates = HashMap::new();
+                for (name, _, array) in leaf_arrays(table) {
+                    if columns.contains(&name) {
+                        if array.data_type() != &DataType::Float64 {
+                            return Err(Error::InvalidInput(format!(
+                                "can only update float64 columns, `{}` is {}",
+                                name,
+                                array.data_type()
+                            )));
+                        }
+                        let one = Float64Array::new_scalar(1.0);
+                        let updated = zip(&matches, &add(&array, &one)?, &array)?;
+                        updates.insert(name, updated);
+                    }
+                }
+                replace_leaves(table, &updates)
+            }
+            Mutation::AddColumns(columns) => {
+                if columns.num_rows() != table.num_rows() {
+                    return Err(Error::InvalidInput(format!(
+                        "{} new column rows for a table of {} rows",
+                        columns.num_rows(),
+                        table.num_rows()
+                    )));
+                }
+                let schema = table.schema();
+                let added = columns.schema();
+                let fields = schema.fields().iter().chain(added.fields()).cloned();
+                let arrays = table.columns().iter().chain(columns.columns()).cloned();
+                Ok(RecordBatch::try_new(
+                    Arc::new(Schema::new(fields.collect::<Vec<_>>())),
+                    arrays.collect(),
+                )?)
+            }
+        }
+    }
+}
+
+// `batch` with the leaves named in `updates` swapped out, struct columns
+// rebuilt around them
+fn replace_leaves(batch: &RecordBatch, updates: &HashMap<String, ArrayRef>) -> Result<RecordBatch> {
+    let mut columns = Vec::new();
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        let column = match (field.data_type(), column.as_struct_opt()) {
+            (DataType::Struct(children), Some(array)) => {
+                let arrays: Vec<ArrayRef> = children
+                    .iter()
+                    .zip(array.columns())
+                    .map(|(child, array)| {
+                        let name = format!("{}.{}", field.name(), child.name());
+                        updates.get(&name).unwrap_or(array).clone()
+                    })
+                    .collect();
+                let nulls = array.nulls().cloned();
+                Arc::new(StructArray::try_new(children.clone(), arrays, nulls)?) as ArrayRef
+            }
+            _ => updates.get(field.name()).unwrap_or(column).clone(),
+        };
+        columns.push(column);
+    }
+    Ok(RecordBatch::try_new(batch.schema(), columns)?)
+}
+
+// A table that is changed in place: a Lance dataset gets new versions, a
+// Parquet file is read, changed and rewritten whole
+#[derive(Debug)]
+pub enum MutableTable {
+    Lance {
+        dir: DatasetDir,
+        config: FormatConfig,
+    },
+    Parquet {
+        bytes: Vec<u8>,
+        config: FormatConfig,
+    },
+}
+
+impl MutableTable {
+    pub async fn create(config: FormatConfig, batch: &RecordBatch) -> Result<MutableTable> {
+        match config {
+            FormatConfig::Lance {
+                version,
+                compression,
+            } => {
+                let dir = DatasetDir::new()?;
+                dataset::write(
+                    &[batch.clone()],
+                    dir.uri(),
+                    version,
+                    compression.as_str(),
+                    DatasetOptions::default(),
+                    WriteMode::Create,
+                )
+                .await?;
+                Ok(MutableTable::Lance { dir, config })
+            }
+            FormatConfig::Parquet { .. } => Ok(MutableTable::Parquet {
+                bytes: config.write(batch.clone()).await?,
+                config,
+            }),
+        }
+    }
+
+    // Everything on disk, old versions included
+    pub fn bytes(&self) -> Result<u64> {
+        match self {
+            MutableTable::Lance { dir, .. } => Ok(disk_usage(dir.path())?.total()),
+            MutableTable::Parquet { bytes, .. } => Ok(bytes.len() as u64),
+        }
+    }
+
+    // Bytes the last change wrote, given `bytes()` from before it: Lance
+    // adds files next to the old ones, Parquet rewrites the whole file
+    pub fn written_since(&self, before: u64) -> Result<u64> {
+        match self {
+            MutableTable::Lance { .. } => Ok(self.bytes()?.saturating_sub(before)),
+            MutableTable::Parquet { bytes, .. } => Ok(bytes.len() as u64),
+        }
+    }
+
+    // Apply `mutation`. Sizes are left to `bytes` and `written_since` so a
+    // timed call measures only the change.
+    pub async fn apply(&mut self, mutation: &Mutation) -> Result<()> {
+        match self {
+            MutableTable::Lance { dir, config } => {
+                match (mutation, *config) {
+                    (
+                        Mutation::Append(batch),
+                        FormatConfig::Lance {
+                            version,
+                            compression,
+                        },
+                    ) => {
+                        dataset::write(
+                            &[batch.clone()],
+                            dir.uri(),
+                            version,
+                            compression.as_str(),
+                            DatasetOptions::default(),
+                            WriteMode::Append,
+                        )
+                        .await?;
+                    }
+                    (Mutation::Delete { .. }, _) => {
+                        let mut opened = dataset::open(dir.uri()).await?;
+                        opened.delete(&mutation.predicate().unwrap()).await?;
+                    }
+                    (Mutation::Update { columns, .. }, _) => {
+                        let opened: Arc<Dataset> = Arc::new(dataset::open(dir.uri()).await?);
+                        let mut builder = UpdateBuilder::new(opened)
+                            .update_where(&mutation.predicate().unwrap())?;
+                        for column in columns {
+                            builder = builder.set(column, &format!("{} + 1", column))?;
+                        }
+                        builder.build()?.execute().await?;
+                    }
+                    (Mutation::AddColumns(columns), FormatConfig::Lance { compression, .. }) => {
+                        dataset::add_columns(dir.uri(), columns, compression.as_str()).await?;
+                    }
+                    _ => unreachable!("lance tables have lance configs"),
+                }
+                Ok(())
+            }
+            MutableTable::Parquet { bytes, config } => {
+                let batches = config.read(bytes).await?;
+                let table = match batches.first() {
+                    Some(first) => arrow_select::concat::concat_batches(&first.schema(), &batches)?,
+                    None => return Err(Error::InvalidInput("empty parquet table".to_string())),
+                };
+                *bytes = config.write(mutation.apply(&table)?).await?;
+                Ok(())
+            }
+        }
+    }
+
+    // Read `columns`, or everything if empty, and return the row count
+    pub async fn scan(&self, columns: &[String]) -> Result<usize> {
+        match self {
+            MutableTable::Lance { dir, .. } => dataset::scan(dir.uri(), columns).await,
+            MutableTable::Parquet { bytes, config } => {
+                let batches = if columns.is_empty() {
+                    config.read(bytes).await?
+                } else {
+                    parquet::read_bytes_projected(bytes, columns)?.0
+                };
+                Ok(batches.iter().map(RecordBatch::num_rows).sum())
+            }
+        }
+    }
+
+    // Take rows by their current position
+    pub async fn take(&self, indices: &[usize], columns: &[String]) -> Result<usize> {
+        match self {
+            MutableTable::Lance { dir, .. } => {
+                let offsets: Vec<u64> = indices.iter().map(|&i| i as u64).collect();
+                Ok(dataset::take(dir.uri(), &offsets, columns)
+                    .await?
+                    .num_rows())
+            }
+            MutableTable::Parquet { bytes, config } => {
+                let batch = if columns.is_empty() {
+                    config.take(bytes, indices).await?
+                } else {
+                    parquet::take_rows_projected(bytes, indices, columns)?.0
+                };
+                Ok(batch.num_rows())
+            }
+        }
+    }
+}
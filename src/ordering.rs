@@ -0,0 +1,179 @@
+use crate::error::{Error, Result};
+use crate::projection::leaf_arrays;
+use arrow::buffer::BooleanBuffer;
+use arrow::compute::{lexsort_to_indices, SortColumn};
+use arrow::row::{RowConverter, SortField};
+use arrow_array::{Array, ArrayRef, RecordBatch, UInt32Array};
+use arrow_select::take::take_record_batch;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::cmp::Reverse;
+
+// How rows are arranged before a batch is written. RLE only pays off on
+// long runs, so the same data can compress very differently per order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowOrder {
+    // As generated
+    Original,
+    // Ascending by these dotted column paths, the first one most significant
+    Sort(Vec<String>),
+    // Lexicographic over "holds the column's most common value" indicators,
+    // the most skewed columns most significant, so rows sharing common
+    // values end up next to each other across many columns at once
+    Cluster,
+    Shuffle(u64),
+}
+
+impl RowOrder {
+    pub fn label(&self) -> String {
+        match self {
+            RowOrder::Original => "original".to_string(),
+            RowOrder::Sort(columns) => format!("sort:{}", columns.join("+")),
+            RowOrder::Cluster => "cluster".to_string(),
+            RowOrder::Shuffle(seed) => format!("shuffle:{}", seed),
+        }
+    }
+}
+
+impl std::str::FromStr for RowOrder {
+    type Err = String;
+
+    // Accepts `original`, `sort:COL[+COL...]`, `cluster` and `shuffle[:SEED]`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "original" => Ok(RowOrder::Original),
+            None if s == "cluster" => Ok(RowOrder::Cluster),
+            None if s == "shuffle" => Ok(RowOrder::Shuffle(crate::data::DEFAULT_SEED)),
+            Some(("sort", columns)) if !columns.is_empty() => Ok(RowOrder::Sort(
+                columns.split('+').map(str::to_string).collect(),
+            )),
+            Some(("shuffle", seed)) => seed
+                .parse()
+                .map(RowOrder::Shuffle)
+                .map_err(|_| format!("invalid shuffle seed `{}`", seed)),
+            _ => Err(format!(
+                "unknown row order `{}`, expected original, sort:COL[+COL], cluster or shuffle[:SEED]",
+                s
+            )),
+        }
+    }
+}
+
+// A reordered batch and where each original row went
+#[derive(Debug, Clone)]
+pub struct Reordered {
+    pub batch: RecordBatch,
+    // `positions[i]` is the new row index of original row `i`
+    pub positions: Vec<usize>,
+}
+
+impl Reordered {
+    // The new indices of `original` rows, in the order given, so a take
+    // returns the same rows as before the reorder
+    pub fn remap(&self, original: &[usize]) -> Result<Vec<usize>> {
+        original
+            .iter()
+            .map(|&i| {
+                self.positions.get(i).copied().ok_or_else(|| {
+                    Error::InvalidInput(format!(
+                        "row {} is out of range for {} rows",
+                        i,
+                        self.positions.len()
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+pub fn reorder(batch: &RecordBatch, order: &RowOrder) -> Result<Reordered> {
+    let num_rows = batch.num_rows();
+    let rows: Vec<u32> = match order {
+        RowOrder::Original => (0..num_rows as u32).collect(),
+        RowOrder::Sort(columns) => {
+            let leaves = leaf_arrays(batch);
+            let sort_columns = columns
+                .iter()
+                .map(|column| {
+                    leaves
+                        .iter()
+                        .find(|(name, _, _)| name == column)
+                        .map(|(_, _, array)| SortColumn {
+                            values: array.clone(),
+                            options: None,
+                        })
+                        .ok_or_else(|| {
+                            Error::InvalidInput(format!("no column `{}` to sort by", column))
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            lexsort_to_indices(&sort_columns, None)?.values().to_vec()
+        }
+        RowOrder::Cluster => cluster(batch)?,
+        RowOrder::Shuffle(seed) => {
+            let mut rows: Vec<u32> = (0..num_rows as u32).collect();
+            rows.shuffle(&mut StdRng::seed_from_u64(*seed));
+            rows
+        }
+    };
+
+    let mut positions = vec![0; num_rows];
+    for (position, &row) in rows.iter().enumerate() {
+        positions[row as usize] = position;
+    }
+    let batch = take_record_batch(batch, &UInt32Array::from(rows))?;
+    Ok(Reordered { batch, positions })
+}
+
+fn cluster(batch: &RecordBatch) -> Result<Vec<u32>> {
+    let num_rows = batch.num_rows();
+    // (rows holding the most common value, which rows those are) per column
+    let mut indicators: Vec<(usize, BooleanBuffer)> = Vec::new();
+    for (_, _, array) in leaf_arrays(batch) {
+        let matches = most_common(&array)?;
+        let count = matches.count_set_bits();
+        // A column where every row matches can't separate rows
+        if count > 1 && count < num_rows {
+            indicators.push((count, matches));
+        }
+    }
+    indicators.sort_by_key(|(count, _)| Reverse(*count));
+
+    // Rows holding common values first, column by column
+    let mut rows: Vec<u32> = (0..num_rows as u32).collect();
+    rows.sort_by(|&a, &b| {
+        indicators
+            .iter()
+            .map(|(_, matches)| matches.value(b as usize).cmp(&matches.value(a as usize)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(rows)
+}
+
+// Which rows of `array` hold its most common non-null value
+fn most_common(array: &ArrayRef) -> Result<BooleanBuffer> {
+    // Row-format values compare by bytes whatever the type
+    let converter = RowConverter::new(vec![SortField::new(array.data_type().clone())])?;
+    let rows = converter.convert_columns(&[array.clone()])?;
+
+    let mut counts = std::collections::HashMap::new();
+    for (i, row) in rows.iter().enumerate() {
+        if array.is_valid(i) {
+            *counts.entry(row).or_insert(0usize) += 1;
+        }
+    }
+    // Ties go to the smallest value, so the order doesn't depend on hashing
+    let most = counts
+        .iter()
+        .max_by(|(a, count_a), (b, count_b)| count_a.cmp(count_b).then(b.cmp(a)));
+    let Some((&common, _)) = most else {
+        return Ok(BooleanBuffer::new_unset(array.len()));
+    };
+    Ok(rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| array.is_valid(i) && row == common)
+        .collect())
+}
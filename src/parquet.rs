@@ -1,64 +1,354 @@
-use arrow_array::RecordBatch;
+use crate::error::Result;
+use crate::inspect::{ChunkEncoding, ColumnSize, ColumnSizes, FileLayout, FileSummary};
+use crate::stats::ReadStats;
+use crate::take::TakePlan;
+use arrow_array::{RecordBatch, RecordBatchReader};
+use bytes::Bytes;
 use parquet::arrow::arrow_reader::{ParquetRecordBatchReaderBuilder, RowSelection, RowSelector};
 use parquet::arrow::arrow_writer::ArrowWriter;
+use parquet::arrow::ProjectionMask;
 use parquet::basic::Compression;
 use parquet::file::properties::WriterProperties;
+use parquet::file::reader::{ChunkReader, Length};
+use parquet::schema::types::SchemaDescriptor;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
-pub fn write_bytes(batch: RecordBatch) -> Vec<u8> {
+pub fn write_bytes(batch: RecordBatch) -> Result<Vec<u8>> {
+    write_bytes_with(batch, Compression::SNAPPY, None)
+}
+
+pub fn write_bytes_with(
+    batch: RecordBatch,
+    compression: Compression,
+    page_size: Option<usize>,
+) -> Result<Vec<u8>> {
+    write_bytes_split(batch, compression, page_size, None)
+}
+
+// Like `write_bytes_with`, starting a new row group every `row_group_size`
+// rows instead of at the writer's default
+pub fn write_bytes_split(
+    batch: RecordBatch,
+    compression: Compression,
+    page_size: Option<usize>,
+    row_group_size: Option<usize>,
+) -> Result<Vec<u8>> {
     // Use Parquet's default encoding selection which automatically chooses:
     // - RLE_DICTIONARY for columns with repeated values
     // - DELTA_BINARY_PACKED for sorted integer columns
     // - PLAIN for other cases
-    let props = WriterProperties::builder()
-        .set_compression(Compression::SNAPPY)
-        .build();
+    let mut props = WriterProperties::builder().set_compression(compression);
+    if let Some(page_size) = page_size {
+        props = props.set_data_page_size_limit(page_size);
+    }
+    if let Some(row_group_size) = row_group_size {
+        props = props.set_max_row_group_size(row_group_size);
+    }
+    let props = props.build();
 
     let mut buffer = Vec::new();
-    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props)).unwrap();
-    writer.write(&batch).unwrap();
-    writer.close().unwrap();
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
 
-    buffer
+    Ok(buffer)
 }
 
-pub fn read_bytes(bytes: &[u8]) -> Vec<RecordBatch> {
-    let reader = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(bytes.to_vec()))
-        .unwrap()
-        .build()
-        .unwrap();
+pub fn read_bytes(bytes: &[u8]) -> Result<Vec<RecordBatch>> {
+    let reader =
+        ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(bytes.to_vec()))?.build()?;
 
-    reader.collect::<Result<Vec<_>, _>>().unwrap()
+    Ok(reader.collect::<Result<Vec<_>, _>>()?)
 }
 
-pub fn take_rows_from_bytes(bytes: &[u8], indices: &[usize]) -> RecordBatch {
-    // Since we only read one row, optimize for this case
-    assert_eq!(indices.len(), 1, "Expected exactly one index");
-    let target_row = indices[0];
-
+pub fn take_rows_from_bytes(bytes: &[u8], indices: &[usize]) -> Result<RecordBatch> {
     let file = bytes::Bytes::from(bytes.to_vec());
-    let mut reader_builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    take(builder, indices)
+}
 
+// Take `indices` in any order, repeats included, with whatever projection
+// `builder` already carries. Indices past the end of the file are an error.
+fn take<T: ChunkReader + 'static>(
+    builder: ParquetRecordBatchReaderBuilder<T>,
+    indices: &[usize],
+) -> Result<RecordBatch> {
     // Get metadata to know total rows
-    let metadata = reader_builder.metadata();
-    let total_rows = metadata.file_metadata().num_rows() as usize;
-    let schema = reader_builder.schema().clone();
+    let total_rows = builder.metadata().file_metadata().num_rows() as usize;
+    let plan = TakePlan::new(indices, total_rows)?;
 
-    if target_row >= total_rows {
-        return RecordBatch::new_empty(schema);
+    // Turn the indices into alternating skip/select runs
+    let mut selectors = Vec::new();
+    let mut position = 0;
+    for &index in &plan.rows {
+        if index > position {
+            selectors.push(RowSelector::skip(index - position));
+        }
+        selectors.push(RowSelector::select(1));
+        position = index + 1;
+    }
+    if total_rows > position {
+        selectors.push(RowSelector::skip(total_rows - position));
     }
-
-    // Build RowSelection for single row
-    let selectors = vec![
-        RowSelector::skip(target_row),
-        RowSelector::select(1),
-        RowSelector::skip(total_rows - target_row - 1),
-    ];
-    let row_selection = RowSelection::from(selectors);
 
     // Apply row selection and build reader
-    reader_builder = reader_builder.with_row_selection(row_selection);
-    let mut reader = reader_builder.build().unwrap();
+    let reader = builder
+        .with_row_selection(RowSelection::from(selectors))
+        .build()?;
+    let schema = reader.schema();
+    let batches = reader.collect::<Result<Vec<_>, _>>()?;
+    plan.restore(arrow_select::concat::concat_batches(&schema, &batches)?)
+}
+
+// A `ChunkReader` over in-memory bytes that counts what the reader pulls out
+// of it, so projected reads can report how much of the file they touched.
+struct CountingReader {
+    data: Bytes,
+    bytes_read: Arc<AtomicU64>,
+    requests: Arc<AtomicU64>,
+}
+
+impl CountingReader {
+    fn new(bytes: &[u8]) -> Self {
+        Self {
+            data: Bytes::from(bytes.to_vec()),
+            bytes_read: Arc::new(AtomicU64::new(0)),
+            requests: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn stats_handle(&self) -> (Arc<AtomicU64>, Arc<AtomicU64>) {
+        (self.bytes_read.clone(), self.requests.clone())
+    }
+}
+
+struct CountingRead<R> {
+    inner: R,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+impl Length for CountingReader {
+    fn len(&self) -> u64 {
+        self.data.len() as u64
+    }
+}
+
+impl ChunkReader for CountingReader {
+    type T = CountingRead<<Bytes as ChunkReader>::T>;
+
+    fn get_read(&self, start: u64) -> parquet::errors::Result<Self::T> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        Ok(CountingRead {
+            inner: self.data.get_read(start)?,
+            bytes_read: self.bytes_read.clone(),
+        })
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.bytes_read.fetch_add(length as u64, Ordering::Relaxed);
+        self.data.get_bytes(start, length)
+    }
+}
 
-    // Read the single row
-    reader.next().unwrap().unwrap()
+fn read_stats(handle: &(Arc<AtomicU64>, Arc<AtomicU64>)) -> ReadStats {
+    ReadStats {
+        bytes_read: handle.0.load(Ordering::Relaxed),
+        requests: handle.1.load(Ordering::Relaxed),
+    }
+}
+
+// Leaf mask for dotted column paths such as `features.feature3`
+fn column_mask(schema: &SchemaDescriptor, columns: &[String]) -> ProjectionMask {
+    let columns: HashSet<&str> = columns.iter().map(String::as_str).collect();
+    let leaves = schema
+        .columns()
+        .iter()
+        .enumerate()
+        .filter(|(_, column)| columns.contains(column.path().string().as_str()))
+        .map(|(i, _)| i);
+    ProjectionMask::leaves(schema, leaves)
+}
+
+pub fn read_bytes_projected(
+    bytes: &[u8],
+    columns: &[String],
+) -> Result<(Vec<RecordBatch>, ReadStats)> {
+    let file = CountingReader::new(bytes);
+    let handle = file.stats_handle();
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let mask = column_mask(builder.parquet_schema(), columns);
+    let reader = builder.with_projection(mask).build()?;
+
+    let batches = reader.collect::<Result<Vec<_>, _>>()?;
+    Ok((batches, read_stats(&handle)))
+}
+
+// Read the file at `path`, only `columns` unless empty. Every read opens
+// the file and parses its footer again.
+pub fn read_file(path: &Path, columns: &[String]) -> Result<Vec<RecordBatch>> {
+    let mut builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    if !columns.is_empty() {
+        let mask = column_mask(builder.parquet_schema(), columns);
+        builder = builder.with_projection(mask);
+    }
+    Ok(builder.build()?.collect::<Result<Vec<_>, _>>()?)
+}
+
+// Take `indices` from the file at `path`, like `read_file`
+pub fn take_from_file(path: &Path, indices: &[usize], columns: &[String]) -> Result<RecordBatch> {
+    let mut builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    if !columns.is_empty() {
+        let mask = column_mask(builder.parquet_schema(), columns);
+        builder = builder.with_projection(mask);
+    }
+    take(builder, indices)
+}
+
+// Take `indices` reading only `columns`.
+pub fn take_rows_projected(
+    bytes: &[u8],
+    indices: &[usize],
+    columns: &[String],
+) -> Result<(RecordBatch, ReadStats)> {
+    let file = CountingReader::new(bytes);
+    let handle = file.stats_handle();
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let mask = column_mask(builder.parquet_schema(), columns);
+    let batch = take(builder.with_projection(mask), indices)?;
+    Ok((batch, read_stats(&handle)))
+}
+
+pub fn summarize(bytes: &[u8]) -> Result<FileSummary> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(bytes.to_vec()))?;
+    let metadata = builder.metadata();
+    Ok(FileSummary {
+        file_bytes: bytes.len(),
+        num_rows: metadata.file_metadata().num_rows() as u64,
+        num_columns: metadata.file_metadata().schema_descr().num_columns(),
+        num_chunks: metadata
+            .row_groups()
+            .iter()
+            .map(|row_group| row_group.num_columns())
+            .sum(),
+    })
+}
+
+pub fn column_sizes(bytes: &[u8]) -> Result<ColumnSizes> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(bytes.to_vec()))?;
+    let metadata = builder.metadata();
+    let descr = metadata.file_metadata().schema_descr();
+
+    let mut columns: Vec<ColumnSize> = descr
+        .columns()
+        .iter()
+        .map(|column| ColumnSize {
+            column: column.path().string(),
+            compressed_bytes: 0,
+            uncompressed_bytes: Some(0),
+        })
+        .collect();
+    for row_group in metadata.row_groups() {
+        for (size, chunk) in columns.iter_mut().zip(row_group.columns()) {
+            size.compressed_bytes += chunk.compressed_size() as u64;
+            size.uncompressed_bytes = size
+                .uncompressed_bytes
+                .map(|bytes| bytes + chunk.uncompressed_size() as u64);
+        }
+    }
+    Ok(ColumnSizes {
+        file_bytes: bytes.len() as u64,
+        columns,
+    })
+}
+
+// Column chunks are split at their first data page: anything before it is
+// the dictionary page. The footer is the Thrift `FileMetaData` plus its
+// length and the trailing magic.
+pub fn layout(bytes: &[u8]) -> Result<FileLayout> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(bytes.to_vec()))?;
+    let metadata = builder.metadata();
+
+    let mut data = 0;
+    let mut dictionary = 0;
+    let mut column_index = 0;
+    let mut offset_index = 0;
+    let mut bloom_filters = 0;
+    for row_group in metadata.row_groups() {
+        for chunk in row_group.columns() {
+            let chunk_bytes = chunk.compressed_size() as u64;
+            let dictionary_bytes = chunk.dictionary_page_offset().map_or(0, |offset| {
+                (chunk.data_page_offset() - offset).max(0) as u64
+            });
+            dictionary += dictionary_bytes;
+            data += chunk_bytes.saturating_sub(dictionary_bytes);
+            column_index += chunk.column_index_length().unwrap_or(0) as u64;
+            offset_index += chunk.offset_index_length().unwrap_or(0) as u64;
+            bloom_filters += chunk.bloom_filter_length().unwrap_or(0) as u64;
+        }
+    }
+
+    // `<metadata><u32 metadata length>PAR1`
+    let trailer = &bytes[bytes.len() - 8..bytes.len() - 4];
+    let footer = u32::from_le_bytes(trailer.try_into().unwrap()) as u64;
+    FileLayout::new(
+        bytes.len() as u64,
+        vec![
+            ("magic", 4),
+            ("data pages", data),
+            ("dictionary pages", dictionary),
+            ("bloom filters", bloom_filters),
+            ("column index", column_index),
+            ("offset index", offset_index),
+            ("footer", footer + 8),
+        ],
+    )
+}
+
+pub fn encodings(bytes: &[u8]) -> Result<Vec<ChunkEncoding>> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(bytes.to_vec()))?;
+    let metadata = builder.metadata();
+
+    let mut chunks = Vec::new();
+    for (index, row_group) in metadata.row_groups().iter().enumerate() {
+        for chunk in row_group.columns() {
+            // e.g. `DATA_PAGE RLE_DICTIONARY x3, DICTIONARY_PAGE PLAIN x1`
+            let detail = chunk
+                .page_encoding_stats()
+                .map(|stats| {
+                    stats
+                        .iter()
+                        .map(|s| format!("{} {} x{}", s.page_type, s.encoding, s.count))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            chunks.push(ChunkEncoding {
+                column: chunk.column_path().string(),
+                chunk: index,
+                num_values: chunk.num_values() as u64,
+                layout: "column chunk".to_string(),
+                encodings: chunk.encodings().iter().map(|e| e.to_string()).collect(),
+                compression: Some(chunk.compression().to_string()),
+                buffer_sizes: vec![chunk.compressed_size() as u64],
+                detail,
+            });
+        }
+    }
+    Ok(chunks)
 }
@@ -0,0 +1,136 @@
+use arrow::array::AsArray;
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, FieldRef, Schema};
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::SeedableRng;
+
+// Which feature columns a projected scan or take should read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnSelection {
+    // Every leaf column, including `uuid`
+    All,
+    // The first `k` feature columns in schema order
+    FirstK(usize),
+    // `k` feature columns drawn uniformly at random
+    RandomK { k: usize, seed: u64 },
+    // Exactly one feature column, i.e. `features.featureN` in the nested schema
+    Single(usize),
+}
+
+impl ColumnSelection {
+    pub fn label(&self) -> String {
+        match self {
+            ColumnSelection::All => "all".to_string(),
+            ColumnSelection::FirstK(k) => format!("first-{}", k),
+            ColumnSelection::RandomK { k, .. } => format!("random-{}", k),
+            ColumnSelection::Single(n) => format!("feature{}", n),
+        }
+    }
+
+    // Resolve the selection to dotted column paths for `schema`, e.g.
+    // `features.feature3` for the nested schema or `feature3` for the flat one.
+    pub fn column_paths(&self, schema: &Schema) -> Vec<String> {
+        match self {
+            ColumnSelection::All => leaf_columns(schema),
+            ColumnSelection::FirstK(k) => feature_columns(schema).into_iter().take(*k).collect(),
+            ColumnSelection::RandomK { k, seed } => {
+                let features = feature_columns(schema);
+                let k = (*k).min(features.len());
+                let mut rng = StdRng::seed_from_u64(*seed);
+                // Keep schema order so both formats return columns identically
+                let mut picked = sample(&mut rng, features.len(), k).into_vec();
+                picked.sort_unstable();
+                picked.into_iter().map(|i| features[i].clone()).collect()
+            }
+            ColumnSelection::Single(n) => {
+                let name = format!("feature{}", n);
+                feature_columns(schema)
+                    .into_iter()
+                    .filter(|path| path.rsplit('.').next() == Some(name.as_str()))
+                    .collect()
+            }
+        }
+    }
+}
+
+// All leaf columns of `schema` as dotted paths, struct children expanded
+pub fn leaf_columns(schema: &Schema) -> Vec<String> {
+    let mut columns = Vec::new();
+    for field in schema.fields() {
+        match field.data_type() {
+            DataType::Struct(children) => {
+                for child in children {
+                    columns.push(format!("{}.{}", field.name(), child.name()));
+                }
+            }
+            _ => columns.push(field.name().clone()),
+        }
+    }
+    columns
+}
+
+// The leaf arrays of `batch` with their fields, named like `leaf_columns`
+pub fn leaf_arrays(batch: &RecordBatch) -> Vec<(String, FieldRef, ArrayRef)> {
+    let mut leaves = Vec::new();
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        match column.as_struct_opt() {
+            Some(children) => {
+                for (child, array) in children.fields().iter().zip(children.columns()) {
+                    let name = format!("{}.{}", field.name(), child.name());
+                    leaves.push((name, child.clone(), array.clone()));
+                }
+            }
+            None => leaves.push((field.name().clone(), field.clone(), column.clone())),
+        }
+    }
+    leaves
+}
+
+// All leaf feature columns of `schema`. The `uuid` key column is not a
+// feature and is never projected by a feature selection.
+pub fn feature_columns(schema: &Schema) -> Vec<String> {
+    leaf_columns(schema)
+        .into_iter()
+        .filter(|path| path != "uuid")
+        .collect()
+}
+
+impl std::str::FromStr for ColumnSelection {
+    type Err = String;
+
+    // Accepts `all`, `first-K`, `random-K`, `random-K:SEED` and `featureN`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |value: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("invalid column selection `{}`", s))
+        };
+
+        if s == "all" {
+            Ok(ColumnSelection::All)
+        } else if let Some(k) = s.strip_prefix("first-") {
+            Ok(ColumnSelection::FirstK(number(k)?))
+        } else if let Some(rest) = s.strip_prefix("random-") {
+            let (k, seed) = match rest.split_once(':') {
+                Some((k, seed)) => (
+                    k,
+                    seed.parse()
+                        .map_err(|_| format!("invalid seed in column selection `{}`", s))?,
+                ),
+                None => (rest, crate::data::DEFAULT_SEED),
+            };
+            Ok(ColumnSelection::RandomK {
+                k: number(k)?,
+                seed,
+            })
+        } else if let Some(n) = s.strip_prefix("feature") {
+            Ok(ColumnSelection::Single(number(n)?))
+        } else {
+            Err(format!(
+                "unknown column selection `{}`, expected all, first-K, random-K or featureN",
+                s
+            ))
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::error::Result;
+use crate::results::Record;
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Where and how a report was produced. Build details come from build.rs, the
+// machine is probed at run time.
+#[derive(Debug, Clone, Serialize)]
+pub struct Provenance {
+    // Tags the records this run produced, as a resumed sweep holds several
+    pub session: String,
+    pub timestamp_unix: u64,
+    pub cpu_model: String,
+    pub cores: usize,
+    pub memory_bytes: Option<u64>,
+    pub os: String,
+    pub kernel: Option<String>,
+    pub rustc: &'static str,
+    pub profile: &'static str,
+    pub opt_level: &'static str,
+    pub benchmark_version: &'static str,
+    pub lance_version: &'static str,
+    pub lance_rev: &'static str,
+    pub parquet_version: &'static str,
+    pub arrow_version: &'static str,
+    pub seed: u64,
+    pub command_line: String,
+    // The full experiment configuration, as given
+    pub config: String,
+}
+
+impl Provenance {
+    pub fn capture(seed: u64, config: String) -> Provenance {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Provenance {
+            session: format!("{}-{}", now.as_nanos(), std::process::id()),
+            timestamp_unix: now.as_secs(),
+            cpu_model: cpu_model().unwrap_or_else(|| "unknown".to_string()),
+            cores: std::thread::available_parallelism().map_or(0, |n| n.get()),
+            memory_bytes: memory_bytes(),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            kernel: command_output("uname", &["-r"]),
+            rustc: env!("BENCH_RUSTC_VERSION"),
+            profile: env!("BENCH_PROFILE"),
+            opt_level: env!("BENCH_OPT_LEVEL"),
+            benchmark_version: env!("CARGO_PKG_VERSION"),
+            lance_version: env!("BENCH_LANCE_VERSION"),
+            lance_rev: env!("BENCH_LANCE_REV"),
+            parquet_version: env!("BENCH_PARQUET_VERSION"),
+            arrow_version: env!("BENCH_ARROW_VERSION"),
+            seed,
+            command_line: std::env::args().collect::<Vec<_>>().join(" "),
+            config,
+        }
+    }
+
+    pub fn write_markdown<W: Write>(&self, out: &mut W) -> Result<()> {
+        writeln!(out, "\n### Environment")?;
+        writeln!(out, "\n| Key | Value |")?;
+        writeln!(out, "|-----|-------|")?;
+        let memory = self
+            .memory_bytes
+            .map_or("unknown".to_string(), |bytes| format!("{} bytes", bytes));
+        let rows = [
+            ("Session", self.session.clone()),
+            ("CPU", self.cpu_model.clone()),
+            ("Cores", self.cores.to_string()),
+            ("Memory", memory),
+            ("OS", self.os.clone()),
+            ("Kernel", self.kernel.clone().unwrap_or_default()),
+            ("rustc", self.rustc.to_string()),
+            (
+                "Profile",
+                format!("{} (opt-level {})", self.profile, self.opt_level),
+            ),
+            (
+                "lance",
+                format!("{} @ {}", self.lance_version, self.lance_rev),
+            ),
+            ("parquet", self.parquet_version.to_string()),
+            ("arrow", self.arrow_version.to_string()),
+            ("Seed", self.seed.to_string()),
+            ("Command", format!("`{}`", self.command_line)),
+        ];
+        for (key, value) in rows {
+            writeln!(out, "| {} | {} |", key, value)?;
+        }
+        writeln!(out, "\n<details><summary>Configuration</summary>\n")?;
+        writeln!(out, "```\n{}\n```\n\n</details>", self.config.trim_end())?;
+        Ok(())
+    }
+
+    // Copies of `records` tagged with this session
+    pub fn stamp(&self, records: &[Record]) -> Vec<Record> {
+        records
+            .iter()
+            .map(|record| Record {
+                session: Some(self.session.clone()),
+                ..record.clone()
+            })
+            .collect()
+    }
+
+    // `results.jsonl` gets `results.provenance.jsonl` next to it, one line
+    // per session that wrote to it
+    pub fn sidecar_path(path: &Path) -> PathBuf {
+        path.with_extension("provenance.jsonl")
+    }
+
+    // Replace the sidecar, for results files written in one go
+    pub fn write_sidecar(&self, path: &Path) -> Result<PathBuf> {
+        let sidecar = Self::sidecar_path(path);
+        self.write_line(File::create(&sidecar)?)?;
+        Ok(sidecar)
+    }
+
+    // Add this session to the sidecar, for results files appended to
+    pub fn append_sidecar(&self, path: &Path) -> Result<PathBuf> {
+        let sidecar = Self::sidecar_path(path);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&sidecar)?;
+        self.write_line(file)?;
+        Ok(sidecar)
+    }
+
+    fn write_line(&self, file: File) -> Result<()> {
+        let mut file = BufWriter::new(file);
+        serde_json::to_writer(&mut file, self).map_err(std::io::Error::from)?;
+        writeln!(file)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let text = String::from_utf8(output.stdout).ok()?;
+    let text = text.trim();
+    (output.status.success() && !text.is_empty()).then(|| text.to_string())
+}
+
+fn cpu_model() -> Option<String> {
+    if let Ok(cpuinfo) = fs::read_to_string("/proc/cpuinfo") {
+        return cpuinfo
+            .lines()
+            .find(|line| line.starts_with("model name"))
+            .and_then(|line| line.split_once(':'))
+            .map(|(_, model)| model.trim().to_string());
+    }
+    command_output("sysctl", &["-n", "machdep.cpu.brand_string"])
+}
+
+fn memory_bytes() -> Option<u64> {
+    if let Ok(meminfo) = fs::read_to_string("/proc/meminfo") {
+        // MemTotal:       16303580 kB
+        return meminfo
+            .lines()
+            .find(|line| line.starts_with("MemTotal:"))
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .map(|kb| kb * 1024);
+    }
+    command_output("sysctl", &["-n", "hw.memsize"]).and_then(|bytes| bytes.parse().ok())
+}
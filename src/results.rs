@@ -0,0 +1,377 @@
+use crate::data::SchemaKind;
+use crate::error::Result;
+use crate::format::FormatConfig;
+use crate::stats::TimingStats;
+use arrow_array::builder::{BooleanBuilder, Float64Builder, StringBuilder, UInt64Builder};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, UInt64Type};
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Seek, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// One measurement with raw numbers, as opposed to the formatted report cells.
+// Fields a step does not measure are left as `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Record {
+    // compress, scan, take, inspect, or the step that failed
+    pub step: String,
+    pub format: String,
+    pub config: String,
+    pub schema: String,
+    pub rows: u64,
+    pub columns: Option<String>,
+    pub file_bytes: Option<u64>,
+    // Compressed bytes of the column named in `columns`
+    pub column_bytes: Option<u64>,
+    // Bytes a write or change put on disk, old files left in place excluded
+    pub bytes_written: Option<u64>,
+    // Layout and encodings a column was written with
+    pub encoding: Option<String>,
+    // In-memory size of the source batch, see `crate::size`
+    pub logical_bytes: Option<u64>,
+    pub physical_bytes: Option<u64>,
+    pub ratio: Option<f64>,
+    pub latency_ms: Option<f64>,
+    // Logical bytes per second, in MB/s
+    pub throughput_mbps: Option<f64>,
+    pub bytes_read: Option<u64>,
+    pub requests: Option<u64>,
+    // Full distribution behind `latency_ms`, which is its median
+    pub timing: Option<TimingStats>,
+    pub error: Option<String>,
+    // Set by sweeps: the cell that produced this record and the dimensions
+    // the fields above don't already carry
+    pub cell: Option<String>,
+    pub page_size: Option<u64>,
+    pub distribution: Option<String>,
+    // Row order the batch was written in, if not as generated
+    pub ordering: Option<String>,
+    // How the table was split, e.g. `files`, and into how many pieces
+    pub layout: Option<String>,
+    pub pieces: Option<u64>,
+    // Workload stage the table was measured after, e.g. `delete 1/10`, or
+    // for schema evolution reads whether the `old` or `new` columns were read
+    pub stage: Option<String>,
+    // File section a layout record covers, e.g. `footer`, and its bytes
+    pub section: Option<String>,
+    pub section_bytes: Option<u64>,
+    // Run that took the measurement, see `Provenance::session`
+    pub session: Option<String>,
+}
+
+impl Record {
+    // The fields naming a configuration and table; callers fill in the step
+    // and what it measured
+    pub fn new(schema: SchemaKind, config: FormatConfig, rows: usize) -> Record {
+        Record {
+            format: config.format().to_string(),
+            config: config.name(),
+            schema: schema.name().to_string(),
+            rows: rows as u64,
+            ..Record::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultFormat {
+    Json,
+    Csv,
+    Parquet,
+}
+
+impl ResultFormat {
+    // Guess from the file extension, defaulting to JSON lines
+    pub fn from_path(path: &Path) -> ResultFormat {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => ResultFormat::Csv,
+            Some("parquet") => ResultFormat::Parquet,
+            _ => ResultFormat::Json,
+        }
+    }
+}
+
+impl FromStr for ResultFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" | "jsonl" => Ok(ResultFormat::Json),
+            "csv" => Ok(ResultFormat::Csv),
+            "parquet" => Ok(ResultFormat::Parquet),
+            _ => Err(format!(
+                "unknown result format `{}`, expected json, csv or parquet",
+                s
+            )),
+        }
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("step", DataType::Utf8, false),
+        Field::new("format", DataType::Utf8, false),
+        Field::new("config", DataType::Utf8, false),
+        Field::new("schema", DataType::Utf8, false),
+        Field::new("rows", DataType::UInt64, false),
+        Field::new("columns", DataType::Utf8, true),
+        Field::new("file_bytes", DataType::UInt64, true),
+        Field::new("column_bytes", DataType::UInt64, true),
+        Field::new("bytes_written", DataType::UInt64, true),
+        Field::new("encoding", DataType::Utf8, true),
+        Field::new("logical_bytes", DataType::UInt64, true),
+        Field::new("physical_bytes", DataType::UInt64, true),
+        Field::new("ratio", DataType::Float64, true),
+        Field::new("latency_ms", DataType::Float64, true),
+        Field::new("throughput_mbps", DataType::Float64, true),
+        Field::new("bytes_read", DataType::UInt64, true),
+        Field::new("requests", DataType::UInt64, true),
+        Field::new("samples", DataType::UInt64, true),
+        Field::new("min_ms", DataType::Float64, true),
+        Field::new("mean_ms", DataType::Float64, true),
+        Field::new("p95_ms", DataType::Float64, true),
+        Field::new("p99_ms", DataType::Float64, true),
+        Field::new("stddev_ms", DataType::Float64, true),
+        Field::new("ci_low_ms", DataType::Float64, true),
+        Field::new("ci_high_ms", DataType::Float64, true),
+        Field::new("noisy", DataType::Boolean, true),
+        Field::new("error", DataType::Utf8, true),
+        Field::new("cell", DataType::Utf8, true),
+        Field::new("page_size", DataType::UInt64, true),
+        Field::new("distribution", DataType::Utf8, true),
+        Field::new("ordering", DataType::Utf8, true),
+        Field::new("layout", DataType::Utf8, true),
+        Field::new("pieces", DataType::UInt64, true),
+        Field::new("stage", DataType::Utf8, true),
+        Field::new("section", DataType::Utf8, true),
+        Field::new("section_bytes", DataType::UInt64, true),
+        Field::new("session", DataType::Utf8, true),
+    ])
+}
+
+pub fn to_record_batch(records: &[Record]) -> Result<RecordBatch> {
+    let strings = |f: fn(&Record) -> Option<&str>| -> ArrayRef {
+        let mut builder = StringBuilder::new();
+        for record in records {
+            builder.append_option(f(record));
+        }
+        Arc::new(builder.finish())
+    };
+    let integers = |f: fn(&Record) -> Option<u64>| -> ArrayRef {
+        let mut builder = UInt64Builder::new();
+        for record in records {
+            builder.append_option(f(record));
+        }
+        Arc::new(builder.finish())
+    };
+    let floats = |f: fn(&Record) -> Option<f64>| -> ArrayRef {
+        let mut builder = Float64Builder::new();
+        for record in records {
+            builder.append_option(f(record));
+        }
+        Arc::new(builder.finish())
+    };
+    let bools = |f: fn(&Record) -> Option<bool>| -> ArrayRef {
+        let mut builder = BooleanBuilder::new();
+        for record in records {
+            builder.append_option(f(record));
+        }
+        Arc::new(builder.finish())
+    };
+
+    let columns = vec![
+        strings(|r| Some(r.step.as_str())),
+        strings(|r| Some(r.format.as_str())),
+        strings(|r| Some(r.config.as_str())),
+        strings(|r| Some(r.schema.as_str())),
+        integers(|r| Some(r.rows)),
+        strings(|r| r.columns.as_deref()),
+        integers(|r| r.file_bytes),
+        integers(|r| r.column_bytes),
+        integers(|r| r.bytes_written),
+        strings(|r| r.encoding.as_deref()),
+        integers(|r| r.logical_bytes),
+        integers(|r| r.physical_bytes),
+        floats(|r| r.ratio),
+        floats(|r| r.latency_ms),
+        floats(|r| r.throughput_mbps),
+        integers(|r| r.bytes_read),
+        integers(|r| r.requests),
+        integers(|r| r.timing.map(|t| t.samples as u64)),
+        floats(|r| r.timing.map(|t| t.min_ms)),
+        floats(|r| r.timing.map(|t| t.mean_ms)),
+        floats(|r| r.timing.map(|t| t.p95_ms)),
+        floats(|r| r.timing.map(|t| t.p99_ms)),
+        floats(|r| r.timing.map(|t| t.stddev_ms)),
+        floats(|r| r.timing.and_then(|t| t.ci_low_ms)),
+        floats(|r| r.timing.and_then(|t| t.ci_high_ms)),
+        bools(|r| r.timing.and_then(|t| t.noisy)),
+        strings(|r| r.error.as_deref()),
+        strings(|r| r.cell.as_deref()),
+        integers(|r| r.page_size),
+        strings(|r| r.distribution.as_deref()),
+        strings(|r| r.ordering.as_deref()),
+        strings(|r| r.layout.as_deref()),
+        integers(|r| r.pieces),
+        strings(|r| r.stage.as_deref()),
+        strings(|r| r.section.as_deref()),
+        integers(|r| r.section_bytes),
+        strings(|r| r.session.as_deref()),
+    ];
+    Ok(RecordBatch::try_new(Arc::new(schema()), columns)?)
+}
+
+pub fn write_records(records: &[Record], path: &Path, format: ResultFormat) -> Result<()> {
+    let batch = to_record_batch(records)?;
+    let file = BufWriter::new(File::create(path)?);
+    // Flushed explicitly, as dropping a BufWriter swallows write errors
+    let mut file = match format {
+        ResultFormat::Json => {
+            let mut writer = arrow::json::LineDelimitedWriter::new(file);
+            writer.write(&batch)?;
+            writer.finish()?;
+            writer.into_inner()
+        }
+        ResultFormat::Csv => {
+            let mut writer = arrow::csv::Writer::new(file);
+            writer.write(&batch)?;
+            writer.into_inner()
+        }
+        ResultFormat::Parquet => {
+            let mut writer = ::parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None)?;
+            writer.write(&batch)?;
+            writer.into_inner()?
+        }
+    };
+    file.flush()?;
+    Ok(())
+}
+
+// Add records to the end of a JSON lines file, creating it if needed. Each
+// call ends on a complete line, so a crash loses at most the records being
+// written.
+pub fn append_records(records: &[Record], path: &Path) -> Result<()> {
+    let batch = to_record_batch(records)?;
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = arrow::json::LineDelimitedWriter::new(BufWriter::new(file));
+    writer.write(&batch)?;
+    writer.finish()?;
+    writer
+        .into_inner()
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_data()?;
+    Ok(())
+}
+
+// Read back a file written by `write_records`. Columns missing from older
+// files are left as `None`; CSV columns are matched by their header name.
+pub fn read_records(path: &Path, format: ResultFormat) -> Result<Vec<Record>> {
+    let schema = Arc::new(schema());
+    let mut file = File::open(path)?;
+    let batches: Vec<RecordBatch> = match format {
+        ResultFormat::Json => arrow::json::ReaderBuilder::new(schema)
+            .build(BufReader::new(file))?
+            .collect::<Result<_, _>>()?,
+        ResultFormat::Csv => {
+            // The CSV reader takes columns by position, so give it just the
+            // ones the header names, in header order
+            let format = arrow::csv::reader::Format::default().with_header(true);
+            let (header, _) = format.infer_schema(&mut file, Some(0))?;
+            file.rewind()?;
+            let fields = header
+                .fields()
+                .iter()
+                .map(|field| schema.field_with_name(field.name()).cloned())
+                .collect::<Result<Vec<_>, _>>()?;
+            arrow::csv::ReaderBuilder::new(Arc::new(Schema::new(fields)))
+                .with_header(true)
+                .build(file)?
+                .collect::<Result<_, _>>()?
+        }
+        ResultFormat::Parquet => {
+            ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)?
+                .build()?
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    let mut records = Vec::new();
+    for batch in &batches {
+        for row in 0..batch.num_rows() {
+            let samples = integer_at(batch, "samples", row);
+            let timing = samples.map(|samples| TimingStats {
+                samples: samples as usize,
+                min_ms: float_at(batch, "min_ms", row).unwrap_or_default(),
+                median_ms: float_at(batch, "latency_ms", row).unwrap_or_default(),
+                mean_ms: float_at(batch, "mean_ms", row).unwrap_or_default(),
+                p95_ms: float_at(batch, "p95_ms", row).unwrap_or_default(),
+                p99_ms: float_at(batch, "p99_ms", row).unwrap_or_default(),
+                stddev_ms: float_at(batch, "stddev_ms", row).unwrap_or_default(),
+                ci_low_ms: float_at(batch, "ci_low_ms", row),
+                ci_high_ms: float_at(batch, "ci_high_ms", row),
+                noisy: bool_at(batch, "noisy", row),
+            });
+            records.push(Record {
+                step: string_at(batch, "step", row).unwrap_or_default(),
+                format: string_at(batch, "format", row).unwrap_or_default(),
+                config: string_at(batch, "config", row).unwrap_or_default(),
+                schema: string_at(batch, "schema", row).unwrap_or_default(),
+                rows: integer_at(batch, "rows", row).unwrap_or_default(),
+                columns: string_at(batch, "columns", row),
+                file_bytes: integer_at(batch, "file_bytes", row),
+                column_bytes: integer_at(batch, "column_bytes", row),
+                bytes_written: integer_at(batch, "bytes_written", row),
+                encoding: string_at(batch, "encoding", row),
+                logical_bytes: integer_at(batch, "logical_bytes", row),
+                physical_bytes: integer_at(batch, "physical_bytes", row),
+                ratio: float_at(batch, "ratio", row),
+                latency_ms: float_at(batch, "latency_ms", row),
+                throughput_mbps: float_at(batch, "throughput_mbps", row),
+                bytes_read: integer_at(batch, "bytes_read", row),
+                requests: integer_at(batch, "requests", row),
+                timing,
+                error: string_at(batch, "error", row),
+                cell: string_at(batch, "cell", row),
+                page_size: integer_at(batch, "page_size", row),
+                distribution: string_at(batch, "distribution", row),
+                ordering: string_at(batch, "ordering", row),
+                layout: string_at(batch, "layout", row),
+                pieces: integer_at(batch, "pieces", row),
+                stage: string_at(batch, "stage", row),
+                section: string_at(batch, "section", row),
+                section_bytes: integer_at(batch, "section_bytes", row),
+                session: string_at(batch, "session", row),
+            });
+        }
+    }
+    Ok(records)
+}
+
+fn string_at(batch: &RecordBatch, name: &str, row: usize) -> Option<String> {
+    let array = batch.column_by_name(name)?.as_string_opt::<i32>()?;
+    array.is_valid(row).then(|| array.value(row).to_string())
+}
+
+fn integer_at(batch: &RecordBatch, name: &str, row: usize) -> Option<u64> {
+    let array = batch
+        .column_by_name(name)?
+        .as_primitive_opt::<UInt64Type>()?;
+    array.is_valid(row).then(|| array.value(row))
+}
+
+fn float_at(batch: &RecordBatch, name: &str, row: usize) -> Option<f64> {
+    let array = batch
+        .column_by_name(name)?
+        .as_primitive_opt::<Float64Type>()?;
+    array.is_valid(row).then(|| array.value(row))
+}
+
+fn bool_at(batch: &RecordBatch, name: &str, row: usize) -> Option<bool> {
+    let array = batch.column_by_name(name)?.as_boolean_opt()?;
+    array.is_valid(row).then(|| array.value(row))
+}
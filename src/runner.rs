@@ -0,0 +1,674 @@
+mod advise;
+mod columns;
+mod compaction;
+mod datasets;
+mod encodings;
+mod evolution;
+mod layout;
+mod mutations;
+mod orderings;
+mod runs;
+mod scaling;
+
+use crate::baseline::{compare, write_deltas};
+use crate::data::{SchemaKind, ValueDistribution};
+use crate::error::{Error, Result};
+use crate::format::{FormatConfig, LanceCompression};
+use crate::inspect::summarize;
+use crate::projection::ColumnSelection;
+use crate::provenance::Provenance;
+use crate::results::{write_records, Record, ResultFormat};
+use crate::size::batch_size;
+use crate::stats::{measure, TimingStats};
+use crate::verify::verify_bytes;
+use arrow_array::RecordBatch;
+use lance_encoding::version::LanceFileVersion;
+use std::io::Write;
+use std::path::PathBuf;
+use tokio::runtime::Runtime;
+
+// The experiment matrix a run covers
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    pub configs: Vec<FormatConfig>,
+    pub schemas: Vec<SchemaKind>,
+    pub rows: Vec<usize>,
+    pub seed: u64,
+    pub repetitions: usize,
+    // Unmeasured runs before each timing
+    pub warmup: usize,
+    pub verify: bool,
+    // Also write every measurement here, in this format
+    pub results: Option<(PathBuf, ResultFormat)>,
+}
+
+// A configuration that errored instead of producing a number
+#[derive(Debug)]
+pub struct Failure {
+    pub schema: String,
+    pub config: String,
+    pub rows: usize,
+    pub step: &'static str,
+    pub error: Error,
+}
+
+// Which reads a read table measures
+#[derive(Debug, Clone, Copy)]
+pub enum ReadOp<'a> {
+    Scan,
+    // Take these row indices; an empty slice means the middle row
+    Take(&'a [usize]),
+}
+
+impl ReadOp<'_> {
+    // Step name of its records
+    pub fn step(&self) -> &'static str {
+        match self {
+            ReadOp::Scan => "scan",
+            ReadOp::Take(_) => "take",
+        }
+    }
+}
+
+// Runs experiments over `RunOptions` and writes Markdown tables to `out`.
+// Failed configurations show up as `failed` cells and are listed at the end
+// instead of aborting the run.
+pub struct Runner<W: Write> {
+    options: RunOptions,
+    provenance: Provenance,
+    out: W,
+    rt: Runtime,
+    failures: Vec<Failure>,
+    records: Vec<Record>,
+}
+
+impl<W: Write> Runner<W> {
+    // The report opens with the environment it was produced in
+    pub fn new(options: RunOptions, provenance: Provenance, mut out: W) -> Result<Self> {
+        provenance.write_markdown(&mut out)?;
+        Ok(Self {
+            options,
+            provenance,
+            out,
+            rt: Runtime::new()?,
+            failures: Vec::new(),
+            records: Vec::new(),
+        })
+    }
+
+    fn header(&mut self, first: &[&str]) -> Result<()> {
+        let names: Vec<String> = self.options.configs.iter().map(|c| c.name()).collect();
+        self.header_for(first, &names)
+    }
+
+    // A header over some other set of configurations
+    fn header_for(&mut self, first: &[&str], names: &[String]) -> Result<()> {
+        let columns: Vec<&str> = first
+            .iter()
+            .copied()
+            .chain(names.iter().map(String::as_str))
+            .collect();
+        writeln!(self.out, "\n| {} |", columns.join(" | "))?;
+        let rule: Vec<String> = columns.iter().map(|c| "-".repeat(c.len())).collect();
+        writeln!(self.out, "|{}|", rule.join("|"))?;
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        schema: SchemaKind,
+        config: FormatConfig,
+        rows: usize,
+        step: &'static str,
+        error: Error,
+    ) {
+        self.record_failure(Record::new(schema, config, rows), step, error);
+    }
+
+    // Like `record`, keeping what else `base` says about the measurement,
+    // e.g. the layout it was taken under
+    fn record_failure(&mut self, base: Record, step: &'static str, error: Error) {
+        self.records.push(Record {
+            step: step.to_string(),
+            error: Some(error.to_string()),
+            ..base.clone()
+        });
+        self.failures.push(Failure {
+            schema: base.schema,
+            config: base.config,
+            rows: base.rows as usize,
+            step,
+            error,
+        });
+    }
+
+    // Write `batch` with every configuration, verifying if asked to. Files
+    // that can't be written or read back are recorded as failed; decoded data
+    // that differs from the source stops the run, as a wrong result must
+    // never make it into a report.
+    fn write_files(
+        &mut self,
+        schema: SchemaKind,
+        batch: &RecordBatch,
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        let mut files = Vec::new();
+        let configs = self.options.configs.clone();
+        for config in configs {
+            let bytes = match self.rt.block_on(config.write(batch.clone())) {
+                Ok(bytes) => bytes,
+                Err(error) => {
+                    self.record(schema, config, batch.num_rows(), "write", error);
+                    files.push(None);
+                    continue;
+                }
+            };
+            if self.options.verify {
+                match self.rt.block_on(verify_bytes(config, batch, &bytes)) {
+                    Ok(()) => {}
+                    Err(Error::Mismatch(mut mismatch)) => {
+                        mismatch.reason = format!(
+                            "{} at {} rows: {}",
+                            config.name(),
+                            batch.num_rows(),
+                            mismatch.reason
+                        );
+                        return Err(Error::Mismatch(mismatch));
+                    }
+                    Err(error) => {
+                        self.record(schema, config, batch.num_rows(), "verify", error);
+                        files.push(None);
+                        continue;
+                    }
+                }
+            }
+            files.push(Some(bytes));
+        }
+        Ok(files)
+    }
+
+    pub fn compress(&mut self) -> Result<()> {
+        writeln!(self.out, "\n=== RLE Compression Benchmark ===")?;
+
+        for schema in self.options.schemas.clone() {
+            writeln!(self.out, "\n### {}", schema.description())?;
+            writeln!(
+                self.out,
+                "Data pattern: {}",
+                schema.pattern(ValueDistribution::Mixed)
+            )?;
+            self.header(&["Rows", "Logical", "Physical"])?;
+
+            for num_rows in self.options.rows.clone() {
+                let batch = schema.generate(num_rows, self.options.seed);
+                // Ratios are taken against the logical size
+                let memory = batch_size(&batch)?;
+
+                let configs = self.options.configs.clone();
+                let sizes: Vec<Option<usize>> = self
+                    .write_files(schema, &batch)?
+                    .iter()
+                    .map(|file| file.as_ref().map(Vec::len))
+                    .collect();
+                let ratios: Vec<Option<f64>> = sizes
+                    .iter()
+                    .map(|size| size.map(|size| memory.logical as f64 / size as f64))
+                    .collect();
+
+                for ((config, size), ratio) in configs.iter().zip(&sizes).zip(&ratios) {
+                    if let (Some(size), Some(ratio)) = (size, ratio) {
+                        self.records.push(Record {
+                            step: "compress".to_string(),
+                            file_bytes: Some(*size as u64),
+                            ratio: Some(*ratio),
+                            logical_bytes: Some(memory.logical),
+                            physical_bytes: Some(memory.physical),
+                            ..Record::new(schema, *config, num_rows)
+                        });
+                    }
+                }
+
+                // Find the best compression ratio
+                let best_ratio = ratios.iter().flatten().fold(0.0_f64, |a, &b| a.max(b));
+
+                // Format size and ratio combined with best one marked
+                let cells: Vec<String> = sizes
+                    .iter()
+                    .zip(&ratios)
+                    .map(|(size, ratio)| match (size, ratio) {
+                        (Some(size), Some(ratio)) if (ratio - best_ratio).abs() < 0.0001 => {
+                            format!("{} (**{:.2}x**)", size, ratio)
+                        }
+                        (Some(size), Some(ratio)) => format!("{} ({:.2}x)", size, ratio),
+                        _ => "failed".to_string(),
+                    })
+                    .collect();
+                writeln!(
+                    self.out,
+                    "| {} | {} | {} | {} |",
+                    num_rows,
+                    memory.logical,
+                    memory.physical,
+                    cells.join(" | ")
+                )?;
+            }
+        }
+
+        writeln!(
+            self.out,
+            "\n**Note**: Best compression ratio for each test is marked with **bold**."
+        )?;
+        Ok(())
+    }
+
+    // Size and speed of every configuration side by side. Throughput is
+    // taken against the logical size of the batch.
+    pub fn summary(&mut self) -> Result<()> {
+        writeln!(self.out, "\n=== Size and Speed ===")?;
+
+        for schema in self.options.schemas.clone() {
+            writeln!(self.out, "\n### {}", schema.description())?;
+            writeln!(
+                self.out,
+                "Timings are the median of {} run(s) after {} warmup run(s), \
+                 `~` marks noisy ones",
+                self.options.repetitions.max(1),
+                self.options.warmup
+            )?;
+            writeln!(
+                self.out,
+                "\n| Rows | Config | Size | Ratio | Write MB/s | Scan MB/s | Take ms |"
+            )?;
+            writeln!(
+                self.out,
+                "|------|--------|------|-------|------------|-----------|---------|"
+            )?;
+            let configs = self.options.configs.clone();
+            let mut distribution = Vec::new();
+
+            for num_rows in self.options.rows.clone() {
+                let batch = schema.generate(num_rows, self.options.seed);
+                let memory = batch_size(&batch)?;
+                let files = self.write_files(schema, &batch)?;
+                let throughput = |ms: f64| memory.logical as f64 / 1e6 / (ms / 1000.0);
+
+                // Sizes are recorded by `compress`; the timings below carry
+                // the file size too
+                for (config, bytes) in configs.iter().copied().zip(&files) {
+                    let Some(bytes) = bytes else {
+                        writeln!(
+                            self.out,
+                            "| {} | {} | failed | failed | failed | failed | failed |",
+                            num_rows,
+                            config.name()
+                        )?;
+                        continue;
+                    };
+                    let ratio = memory.logical as f64 / bytes.len() as f64;
+
+                    let middle = [num_rows / 2];
+                    let timings = [
+                        (
+                            "write",
+                            self.time(|| self.rt.block_on(config.write(batch.clone())))
+                                .map(|(timing, _)| timing),
+                        ),
+                        (
+                            "scan",
+                            self.time(|| self.rt.block_on(config.read(bytes)))
+                                .map(|(timing, _)| timing),
+                        ),
+                        (
+                            "take",
+                            self.time(|| self.rt.block_on(config.take(bytes, &middle)))
+                                .map(|(timing, _)| timing),
+                        ),
+                    ];
+
+                    let mut cells = Vec::new();
+                    for (step, timing) in timings {
+                        match timing {
+                            Ok(timing) => {
+                                // Takes are reported as latency, the rest as throughput
+                                let (cell, throughput_mbps) = if step == "take" {
+                                    (format!("{:.2}", timing.median_ms), None)
+                                } else {
+                                    let mbps = throughput(timing.median_ms);
+                                    (format!("{:.1}", mbps), Some(mbps))
+                                };
+                                self.records.push(Record {
+                                    step: step.to_string(),
+                                    file_bytes: Some(bytes.len() as u64),
+                                    logical_bytes: Some(memory.logical),
+                                    latency_ms: Some(timing.median_ms),
+                                    throughput_mbps,
+                                    timing: Some(timing),
+                                    ..Record::new(schema, config, num_rows)
+                                });
+                                distribution.push(format!(
+                                    "| {} | {} | {} | {} |",
+                                    num_rows,
+                                    config.name(),
+                                    step,
+                                    distribution_cells(&timing)
+                                ));
+                                cells.push(noisy_mark(cell, &timing));
+                            }
+                            Err(error) => {
+                                self.record(schema, config, num_rows, step, error);
+                                cells.push("failed".to_string());
+                            }
+                        }
+                    }
+
+                    writeln!(
+                        self.out,
+                        "| {} | {} | {} | {:.2}x | {} |",
+                        num_rows,
+                        config.name(),
+                        bytes.len(),
+                        ratio,
+                        cells.join(" | ")
+                    )?;
+                }
+            }
+
+            writeln!(self.out, "\nTiming distribution in ms")?;
+            writeln!(
+                self.out,
+                "\n| Rows | Config | Step | Min | Median | p95 | p99 | Stddev | 95% CI | Noisy |"
+            )?;
+            writeln!(
+                self.out,
+                "|------|--------|------|-----|--------|-----|-----|--------|--------|-------|"
+            )?;
+            for line in distribution {
+                writeln!(self.out, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn time<T>(&self, op: impl FnMut() -> Result<T>) -> Result<(TimingStats, T)> {
+        let options = &self.options;
+        measure(options.warmup, options.repetitions, options.seed, op)
+    }
+
+    // Call `table` with a batch of every schema and row count of the run.
+    // Each schema opens with a heading and whatever `intro` writes under it,
+    // e.g. the header of its table.
+    fn each_table(
+        &mut self,
+        mut intro: impl FnMut(&mut Self) -> Result<()>,
+        mut table: impl FnMut(&mut Self, SchemaKind, &RecordBatch) -> Result<()>,
+    ) -> Result<()> {
+        for schema in self.options.schemas.clone() {
+            writeln!(self.out, "\n### {}", schema.description())?;
+            intro(self)?;
+            for num_rows in self.options.rows.clone() {
+                let batch = schema.generate(num_rows, self.options.seed);
+                table(self, schema, &batch)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn reads(&mut self, ops: &[ReadOp], selections: &[ColumnSelection]) -> Result<()> {
+        writeln!(self.out, "\n=== Read Benchmark ===")?;
+
+        for schema in self.options.schemas.clone() {
+            writeln!(self.out, "\n### {}", schema.description())?;
+            writeln!(
+                self.out,
+                "Cells show median latency over {} run(s) with its 95% CI / bytes read, \
+                 `~` marks noisy timings",
+                self.options.repetitions.max(1)
+            )?;
+            self.header(&["Rows", "Columns", "Op"])?;
+            let configs = self.options.configs.clone();
+
+            for num_rows in self.options.rows.clone() {
+                let batch = schema.generate(num_rows, self.options.seed);
+                let files = self.write_files(schema, &batch)?;
+
+                for selection in selections {
+                    let columns = selection.column_paths(&batch.schema());
+
+                    for op in ops {
+                        let middle = [num_rows / 2];
+                        let label = op.step();
+                        let indices: &[usize] = match op {
+                            ReadOp::Scan => &[],
+                            ReadOp::Take(indices) if !indices.is_empty() => indices,
+                            ReadOp::Take(_) => &middle,
+                        };
+
+                        let mut cells = Vec::new();
+                        for (config, bytes) in configs.iter().zip(&files) {
+                            let Some(bytes) = bytes else {
+                                cells.push("failed".to_string());
+                                continue;
+                            };
+                            let result = self.time(|| match op {
+                                ReadOp::Scan => {
+                                    Ok(self.rt.block_on(config.read_projected(bytes, &columns))?.1)
+                                }
+                                ReadOp::Take(_) => Ok(self
+                                    .rt
+                                    .block_on(config.take_projected(bytes, indices, &columns))?
+                                    .1),
+                            });
+                            match result {
+                                Ok((timing, stats)) => {
+                                    self.records.push(Record {
+                                        step: label.to_string(),
+                                        columns: Some(selection.label()),
+                                        file_bytes: Some(bytes.len() as u64),
+                                        latency_ms: Some(timing.median_ms),
+                                        bytes_read: Some(stats.bytes_read),
+                                        requests: Some(stats.requests),
+                                        timing: Some(timing),
+                                        ..Record::new(schema, *config, num_rows)
+                                    });
+                                    let cell = format!(
+                                        "{:.2} ms {} / {} B",
+                                        timing.median_ms,
+                                        ci_cell(&timing, 2),
+                                        stats.bytes_read
+                                    );
+                                    cells.push(noisy_mark(cell, &timing));
+                                }
+                                Err(error) => {
+                                    self.record(schema, *config, num_rows, label, error);
+                                    cells.push("failed".to_string());
+                                }
+                            }
+                        }
+
+                        writeln!(
+                            self.out,
+                            "| {} | {} | {} | {} |",
+                            num_rows,
+                            selection.label(),
+                            label,
+                            cells.join(" | ")
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // A table of measures by configuration from `(measure, cell)` pairs,
+    // measures in first-seen order and missing ones shown as failed
+    fn measure_table(
+        &mut self,
+        names: &[String],
+        per_config: &[Vec<(String, String)>],
+    ) -> Result<()> {
+        let mut measures: Vec<&String> = Vec::new();
+        for (measure, _) in per_config.iter().flatten() {
+            if !measures.contains(&measure) {
+                measures.push(measure);
+            }
+        }
+        self.header_for(&["Measure"], names)?;
+        for measure in measures {
+            let cells: Vec<&str> = per_config
+                .iter()
+                .map(|cells| {
+                    cells
+                        .iter()
+                        .find(|(name, _)| name == measure)
+                        .map_or("failed", |(_, cell)| cell.as_str())
+                })
+                .collect();
+            writeln!(self.out, "| {} | {} |", measure, cells.join(" | "))?;
+        }
+        Ok(())
+    }
+
+    // The Lance configurations of the run, for modes only Lance supports
+    fn lance_configs(&self) -> Result<Vec<(FormatConfig, LanceFileVersion, LanceCompression)>> {
+        let configs: Vec<_> = self
+            .options
+            .configs
+            .iter()
+            .filter_map(|config| match *config {
+                FormatConfig::Lance {
+                    version,
+                    compression,
+                } => Some((*config, version, compression)),
+                FormatConfig::Parquet { .. } => None,
+            })
+            .collect();
+        if configs.is_empty() {
+            return Err(Error::InvalidInput(
+                "this mode needs at least one lance configuration".to_string(),
+            ));
+        }
+        Ok(configs)
+    }
+
+    pub fn inspect(&mut self) -> Result<()> {
+        writeln!(self.out, "\n=== File Inspection ===")?;
+
+        for schema in self.options.schemas.clone() {
+            writeln!(self.out, "\n### {}", schema.description())?;
+            writeln!(
+                self.out,
+                "Cells show file bytes / leaf columns / pages or column chunks"
+            )?;
+            self.header(&["Rows"])?;
+            let configs = self.options.configs.clone();
+
+            for num_rows in self.options.rows.clone() {
+                let batch = schema.generate(num_rows, self.options.seed);
+                let files = self.write_files(schema, &batch)?;
+
+                let mut cells = Vec::new();
+                for (config, bytes) in configs.iter().copied().zip(&files) {
+                    let Some(bytes) = bytes else {
+                        cells.push("failed".to_string());
+                        continue;
+                    };
+                    match self.rt.block_on(summarize(config, bytes)) {
+                        Ok(summary) => {
+                            self.records.push(Record {
+                                step: "inspect".to_string(),
+                                file_bytes: Some(summary.file_bytes as u64),
+                                ..Record::new(schema, config, num_rows)
+                            });
+                            cells.push(format!(
+                                "{} / {} / {}",
+                                summary.file_bytes, summary.num_columns, summary.num_chunks
+                            ))
+                        }
+                        Err(error) => {
+                            self.record(schema, config, num_rows, "inspect", error);
+                            cells.push("failed".to_string());
+                        }
+                    }
+                }
+                writeln!(self.out, "| {} | {} |", num_rows, cells.join(" | "))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    // Compare everything measured so far against a saved baseline and return
+    // the number of regressions
+    pub fn compare(
+        &mut self,
+        name: &str,
+        baseline: &[Record],
+        threshold_pct: f64,
+    ) -> Result<usize> {
+        let deltas = compare(baseline, &self.records, threshold_pct);
+        write_deltas(&mut self.out, name, &deltas)?;
+        Ok(deltas.iter().filter(|d| d.regression).count())
+    }
+
+    // Print the failed configurations, if any, and hand them back
+    pub fn finish(mut self) -> Result<Vec<Failure>> {
+        if !self.failures.is_empty() {
+            writeln!(self.out, "\n### Failed configurations")?;
+            writeln!(self.out, "\n| Schema | Config | Rows | Step | Error |")?;
+            writeln!(self.out, "|--------|--------|------|------|-------|")?;
+            for failure in &self.failures {
+                writeln!(
+                    self.out,
+                    "| {} | {} | {} | {} | {} |",
+                    failure.schema, failure.config, failure.rows, failure.step, failure.error
+                )?;
+            }
+        }
+        self.out.flush()?;
+        if let Some((path, format)) = &self.options.results {
+            write_records(&self.provenance.stamp(&self.records), path, *format)?;
+            self.provenance.write_sidecar(path)?;
+        }
+        Ok(self.failures)
+    }
+}
+
+fn noisy_mark(cell: String, timing: &TimingStats) -> String {
+    if timing.noisy == Some(true) {
+        format!("~{}", cell)
+    } else {
+        cell
+    }
+}
+
+fn distribution_cells(timing: &TimingStats) -> String {
+    format!(
+        "{:.3} | {:.3} | {:.3} | {:.3} | {:.3} | {} | {}",
+        timing.min_ms,
+        timing.median_ms,
+        timing.p95_ms,
+        timing.p99_ms,
+        timing.stddev_ms,
+        ci_cell(timing, 3),
+        match timing.noisy {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "-",
+        }
+    )
+}
+
+// `[low, high]`, or `-` when there were too few samples for an interval
+fn ci_cell(timing: &TimingStats, precision: usize) -> String {
+    match timing.ci_low_ms.zip(timing.ci_high_ms) {
+        Some((low, high)) => format!("[{:.*}, {:.*}]", precision, low, precision, high),
+        None => "-".to_string(),
+    }
+}
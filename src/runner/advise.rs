@@ -0,0 +1,246 @@
+use super::Runner;
+use crate::advise::{choose, Advice, Candidate, ColumnCandidates, Objective};
+use crate::data::SchemaKind;
+use crate::error::{Error, Result};
+use crate::format::{FormatConfig, LanceCompression};
+use crate::lance;
+use crate::projection::leaf_arrays;
+use crate::results::Record;
+use arrow_array::RecordBatch;
+use arrow_schema::Schema;
+use lance_encoding::version::LanceFileVersion;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+impl<W: Write> Runner<W> {
+    // Try every Lance compression of the run on each column of a sample, one
+    // column per file, and pick per column under `objective`. The advised
+    // schema is then checked by writing the whole sample with it.
+    pub fn advise(
+        &mut self,
+        sample_rows: usize,
+        objective: Objective,
+        metadata_out: Option<&Path>,
+        schema_out: Option<&Path>,
+    ) -> Result<()> {
+        let mut versions: Vec<LanceFileVersion> = Vec::new();
+        let mut compressions: Vec<LanceCompression> = Vec::new();
+        for config in &self.options.configs {
+            if let FormatConfig::Lance {
+                version,
+                compression,
+            } = config
+            {
+                if !versions.contains(version) {
+                    versions.push(*version);
+                }
+                if !compressions.contains(compression) {
+                    compressions.push(*compression);
+                }
+            }
+        }
+        if versions.is_empty() {
+            return Err(Error::InvalidInput(
+                "advise needs at least one lance configuration".to_string(),
+            ));
+        }
+
+        writeln!(self.out, "\n=== Compression Advice ===")?;
+        // Each version chooses its encodings differently, so each gets its
+        // own advice, and its own output files when there are several
+        for &version in &versions {
+            writeln!(
+                self.out,
+                "\nObjective: {:?}, lance v{}, {} sample rows",
+                objective, version, sample_rows
+            )?;
+            for schema in self.options.schemas.clone() {
+                let label = if versions.len() > 1 {
+                    format!("{}-v{}", schema.name(), version)
+                } else {
+                    schema.name().to_string()
+                };
+                writeln!(
+                    self.out,
+                    "\n### {}, lance v{}",
+                    schema.description(),
+                    version
+                )?;
+                let (advice, advised) =
+                    self.advise_schema(schema, version, sample_rows, objective, &compressions)?;
+                if let Some(path) = metadata_out {
+                    let path = schema_path(path, &label);
+                    let mut file = BufWriter::new(File::create(&path)?);
+                    serde_json::to_writer_pretty(&mut file, &advice.metadata())
+                        .map_err(std::io::Error::from)?;
+                    writeln!(file)?;
+                    file.flush()?;
+                    writeln!(self.out, "\nColumn metadata written to {}", path.display())?;
+                }
+                if let Some(path) = schema_out {
+                    // An Arrow IPC file with the schema and no batches
+                    let path = schema_path(path, &label);
+                    let file = File::create(&path)?;
+                    arrow::ipc::writer::FileWriter::try_new(file, &advised)?.finish()?;
+                    writeln!(self.out, "\nAdvised schema written to {}", path.display())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Try every compression on each column of a sample of `schema` and
+    // report the advice, returned with the sample's schema annotated by it
+    fn advise_schema(
+        &mut self,
+        schema: SchemaKind,
+        version: LanceFileVersion,
+        sample_rows: usize,
+        objective: Objective,
+        compressions: &[LanceCompression],
+    ) -> Result<(Advice, Schema)> {
+        let sample = schema.generate(sample_rows, self.options.seed);
+        let mut columns = Vec::new();
+        for (name, field, array) in leaf_arrays(&sample) {
+            let single = RecordBatch::try_new(
+                Arc::new(Schema::new(vec![field.as_ref().clone()])),
+                vec![array],
+            )?;
+            let mut candidates = Vec::new();
+            for &compression in compressions {
+                let config = FormatConfig::Lance {
+                    version,
+                    compression,
+                };
+                let written = self.rt.block_on(lance::write_bytes_with(
+                    single.clone(),
+                    version,
+                    compression.as_str(),
+                    None,
+                ));
+                let timed = written.and_then(|bytes| {
+                    let (timing, _) = self.time(|| self.rt.block_on(lance::read_bytes(&bytes)))?;
+                    Ok((bytes.len() as u64, timing.median_ms))
+                });
+                match timed {
+                    Ok((bytes, scan_ms)) => candidates.push(Candidate {
+                        compression,
+                        bytes,
+                        scan_ms,
+                    }),
+                    Err(error) => self.record(schema, config, sample_rows, "advise", error),
+                }
+            }
+            if !candidates.is_empty() {
+                columns.push(ColumnCandidates {
+                    column: name,
+                    candidates,
+                });
+            }
+        }
+
+        let advice = choose(&columns, objective);
+        for choice in &advice.choices {
+            let config = FormatConfig::Lance {
+                version,
+                compression: choice.candidate.compression,
+            };
+            self.records.push(Record {
+                step: "advise".to_string(),
+                columns: Some(choice.column.clone()),
+                column_bytes: Some(choice.candidate.bytes),
+                latency_ms: Some(choice.candidate.scan_ms),
+                encoding: Some(choice.candidate.compression.as_str().to_string()),
+                ..Record::new(schema, config, sample_rows)
+            });
+        }
+        self.write_advice(&advice, &columns, compressions, &sample, version)?;
+        let advised = advice.apply(&sample.schema());
+        Ok((advice, advised))
+    }
+
+    fn write_advice(
+        &mut self,
+        advice: &Advice,
+        columns: &[ColumnCandidates],
+        compressions: &[LanceCompression],
+        sample: &RecordBatch,
+        version: LanceFileVersion,
+    ) -> Result<()> {
+        writeln!(self.out, "\n| Compression | Columns chosen |")?;
+        writeln!(self.out, "|-------------|----------------|")?;
+        for compression in compressions {
+            let chosen = advice
+                .choices
+                .iter()
+                .filter(|c| c.candidate.compression == *compression)
+                .count();
+            writeln!(self.out, "| {} | {} |", compression.as_str(), chosen)?;
+        }
+        if !advice.within_budget {
+            writeln!(
+                self.out,
+                "\nNo combination meets the budget, closest shown."
+            )?;
+        }
+
+        // Per-column totals, then the whole sample written as one file
+        writeln!(
+            self.out,
+            "\n| Plan | Column bytes | Column scan ms | File bytes |"
+        )?;
+        writeln!(
+            self.out,
+            "|------|--------------|----------------|------------|"
+        )?;
+        let advised = RecordBatch::try_new(
+            Arc::new(advice.apply(&sample.schema())),
+            sample.columns().to_vec(),
+        )?;
+        let file_bytes = self
+            .rt
+            .block_on(lance::write_annotated_bytes(advised, version, None))
+            .map_or("failed".to_string(), |bytes| bytes.len().to_string());
+        writeln!(
+            self.out,
+            "| advised | {} | {:.3} | {} |",
+            advice.total_bytes, advice.total_scan_ms, file_bytes
+        )?;
+        for compression in compressions {
+            let uniform: Vec<&Candidate> = columns
+                .iter()
+                .filter_map(|c| c.candidates.iter().find(|c| c.compression == *compression))
+                .collect();
+            let file_bytes = self
+                .rt
+                .block_on(lance::write_bytes_with(
+                    sample.clone(),
+                    version,
+                    compression.as_str(),
+                    None,
+                ))
+                .map_or("failed".to_string(), |bytes| bytes.len().to_string());
+            writeln!(
+                self.out,
+                "| all {} | {} | {:.3} | {} |",
+                compression.as_str(),
+                uniform.iter().map(|c| c.bytes).sum::<u64>(),
+                uniform.iter().map(|c| c.scan_ms).sum::<f64>(),
+                file_bytes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// `advice.json` becomes `advice-nested.json`, one file per schema
+fn schema_path(path: &Path, schema: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, schema, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, schema),
+    };
+    path.with_file_name(name)
+}
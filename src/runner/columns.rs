@@ -0,0 +1,138 @@
+use super::Runner;
+use crate::data::SchemaKind;
+use crate::error::Result;
+use crate::inspect::{column_sizes, ColumnSizes};
+use crate::results::Record;
+use arrow_array::RecordBatch;
+use std::io::Write;
+
+impl<W: Write> Runner<W> {
+    // Compressed bytes per leaf column: aggregates over all columns, then the
+    // `top` largest columns of each configuration side by side
+    pub fn columns(&mut self, top: usize) -> Result<()> {
+        writeln!(self.out, "\n=== Per-Column Size ===")?;
+
+        self.each_table(
+            |_| Ok(()),
+            |runner, schema, batch| runner.column_table(schema, batch, top),
+        )
+    }
+
+    fn column_table(&mut self, schema: SchemaKind, batch: &RecordBatch, top: usize) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let configs = self.options.configs.clone();
+        let files = self.write_files(schema, batch)?;
+
+        let mut sizes: Vec<Option<ColumnSizes>> = Vec::new();
+        for (config, bytes) in configs.iter().copied().zip(&files) {
+            let Some(bytes) = bytes else {
+                sizes.push(None);
+                continue;
+            };
+            match self.rt.block_on(column_sizes(config, bytes)) {
+                Ok(columns) => {
+                    for column in &columns.columns {
+                        self.records.push(Record {
+                            step: "column".to_string(),
+                            columns: Some(column.column.clone()),
+                            file_bytes: Some(columns.file_bytes),
+                            column_bytes: Some(column.compressed_bytes),
+                            ..Record::new(schema, config, num_rows)
+                        });
+                    }
+                    sizes.push(Some(columns));
+                }
+                Err(error) => {
+                    self.record(schema, config, num_rows, "column", error);
+                    sizes.push(None);
+                }
+            }
+        }
+
+        writeln!(self.out, "\n{} rows, bytes per column", num_rows)?;
+        self.header(&["Metric"])?;
+        let metrics: [(&str, fn(&ColumnSizes) -> String); 5] = [
+            ("columns", |s| s.columns.len().to_string()),
+            ("min", |s| column_stat(s, |bytes| bytes[0])),
+            ("median", |s| column_stat(s, |bytes| bytes[bytes.len() / 2])),
+            ("max", |s| column_stat(s, |bytes| bytes[bytes.len() - 1])),
+            ("metadata + footer", |s| {
+                format!(
+                    "{} ({:.1}%)",
+                    s.overhead_bytes(),
+                    s.overhead_bytes() as f64 * 100.0 / s.file_bytes as f64
+                )
+            }),
+        ];
+        for (metric, cell) in metrics {
+            let cells: Vec<String> = sizes
+                .iter()
+                .map(|s| s.as_ref().map_or("failed".to_string(), cell))
+                .collect();
+            writeln!(self.out, "| {} | {} |", metric, cells.join(" | "))?;
+        }
+
+        writeln!(self.out, "\nLargest {} columns", top)?;
+        self.header(&["Rank"])?;
+        let worst: Vec<Vec<String>> = sizes
+            .iter()
+            .map(|s| {
+                s.as_ref().map_or(Vec::new(), |s| {
+                    s.worst(top)
+                        .iter()
+                        .map(|c| format!("{} ({})", c.column, c.compressed_bytes))
+                        .collect()
+                })
+            })
+            .collect();
+        for rank in 0..worst.iter().map(Vec::len).max().unwrap_or(0) {
+            let cells: Vec<&str> = worst
+                .iter()
+                .map(|w| w.get(rank).map_or("", String::as_str))
+                .collect();
+            writeln!(self.out, "| {} | {} |", rank + 1, cells.join(" | "))?;
+        }
+
+        let mut structs: Vec<String> = Vec::new();
+        for (name, _) in sizes.iter().flatten().flat_map(|s| s.struct_totals()) {
+            if !structs.contains(&name) {
+                structs.push(name);
+            }
+        }
+        if !structs.is_empty() {
+            writeln!(self.out, "\nBytes per struct, all of its columns together")?;
+            self.header(&["Struct"])?;
+            for name in &structs {
+                let cells: Vec<String> = sizes
+                    .iter()
+                    .map(|s| {
+                        s.as_ref().map_or("failed".to_string(), |s| {
+                            s.struct_totals()
+                                .into_iter()
+                                .find(|(other, _)| other == name)
+                                .map_or("-".to_string(), |(_, bytes)| {
+                                    format!(
+                                        "{} ({:.1}%)",
+                                        bytes,
+                                        bytes as f64 * 100.0 / s.file_bytes as f64
+                                    )
+                                })
+                        })
+                    })
+                    .collect();
+                writeln!(self.out, "| {} | {} |", name, cells.join(" | "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Pick a value from the ascending per-column byte counts, if there are any
+fn column_stat(sizes: &ColumnSizes, pick: fn(&[u64]) -> u64) -> String {
+    let mut bytes: Vec<u64> = sizes.columns.iter().map(|c| c.compressed_bytes).collect();
+    if bytes.is_empty() {
+        return "-".to_string();
+    }
+    bytes.sort_unstable();
+    pick(&bytes).to_string()
+}
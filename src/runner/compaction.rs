@@ -0,0 +1,188 @@
+use super::{noisy_mark, Runner};
+use crate::data::SchemaKind;
+use crate::dataset::{self, disk_usage, DatasetDir, DatasetOptions};
+use crate::error::Result;
+use crate::format::{FormatConfig, LanceCompression};
+use crate::results::Record;
+use ::lance::dataset::WriteMode;
+use arrow_array::RecordBatch;
+use lance_encoding::version::LanceFileVersion;
+use std::io::Write;
+use std::time::Instant;
+
+impl<W: Write> Runner<W> {
+    // Build each table from many small appends, a fragment each, then
+    // compact it. Reads are measured before and after; compaction runs once
+    // per table as it changes the dataset.
+    pub fn compaction(&mut self, append_rows: usize, takes: usize) -> Result<()> {
+        let configs = self.lance_configs()?;
+        writeln!(self.out, "\n=== Compaction Benchmark ===")?;
+        writeln!(
+            self.out,
+            "\nAppends of {} rows; reads include opening the dataset, compaction is a single run",
+            append_rows
+        )?;
+        let names: Vec<String> = configs.iter().map(|(c, _, _)| c.name()).collect();
+
+        self.each_table(
+            |_| Ok(()),
+            |runner, schema, batch| {
+                runner.compaction_table(schema, batch, &configs, &names, append_rows, takes)
+            },
+        )
+    }
+
+    fn compaction_table(
+        &mut self,
+        schema: SchemaKind,
+        batch: &RecordBatch,
+        configs: &[(FormatConfig, LanceFileVersion, LanceCompression)],
+        names: &[String],
+        append_rows: usize,
+        takes: usize,
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let offsets: Vec<u64> = (0..takes.min(num_rows))
+            .map(|i| (i * num_rows / takes.min(num_rows)) as u64)
+            .collect();
+
+        let mut per_config = Vec::new();
+        for &(config, version, compression) in configs {
+            let mut cells = Vec::new();
+            if let Err(error) = self.compaction_cells(
+                schema,
+                config,
+                (version, compression.as_str()),
+                batch,
+                append_rows,
+                &offsets,
+                &mut cells,
+            ) {
+                self.record(schema, config, num_rows, "compact", error);
+            }
+            per_config.push(cells);
+        }
+
+        writeln!(self.out, "\n{} rows", num_rows)?;
+        self.measure_table(names, &per_config)?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn compaction_cells(
+        &mut self,
+        schema: SchemaKind,
+        config: FormatConfig,
+        (version, compression): (LanceFileVersion, &str),
+        batch: &RecordBatch,
+        append_rows: usize,
+        offsets: &[u64],
+        cells: &mut Vec<(String, String)>,
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let dir = DatasetDir::new()?;
+        let start = Instant::now();
+        for (i, first) in (0..num_rows).step_by(append_rows.max(1)).enumerate() {
+            let piece = batch.slice(first, append_rows.max(1).min(num_rows - first));
+            let mode = if i == 0 {
+                WriteMode::Create
+            } else {
+                WriteMode::Append
+            };
+            self.rt.block_on(dataset::write(
+                &[piece],
+                dir.uri(),
+                version,
+                compression,
+                DatasetOptions::default(),
+                mode,
+            ))?;
+        }
+        let append_ms = start.elapsed().as_secs_f64() * 1000.0;
+        self.records.push(Record {
+            step: "compaction-append".to_string(),
+            file_bytes: Some(disk_usage(dir.path())?.total()),
+            latency_ms: Some(append_ms),
+            ..Record::new(schema, config, num_rows)
+        });
+        cells.push(("append ms".to_string(), format!("{:.2}", append_ms)));
+        let before =
+            self.compaction_reads(schema, config, num_rows, &dir, "fragmented", offsets, cells)?;
+
+        let start = Instant::now();
+        let (removed, added) = self.rt.block_on(dataset::compact(dir.uri()))?;
+        let compact_ms = start.elapsed().as_secs_f64() * 1000.0;
+        self.records.push(Record {
+            step: "compact".to_string(),
+            file_bytes: Some(disk_usage(dir.path())?.total()),
+            latency_ms: Some(compact_ms),
+            ..Record::new(schema, config, num_rows)
+        });
+        cells.push(("compact ms".to_string(), format!("{:.2}", compact_ms)));
+        cells.push((
+            "fragments rewritten".to_string(),
+            format!("{} -> {}", removed, added),
+        ));
+
+        let after =
+            self.compaction_reads(schema, config, num_rows, &dir, "compacted", offsets, cells)?;
+        cells.push((
+            "live data change".to_string(),
+            format!(
+                "{:+.1}%",
+                (after as f64 - before as f64) * 100.0 / before.max(1) as f64
+            ),
+        ));
+        Ok(())
+    }
+
+    // Fragment count, sizes and read latencies of the dataset as it is now,
+    // labelled with `stage`. Returns the live data bytes.
+    #[allow(clippy::too_many_arguments)]
+    fn compaction_reads(
+        &mut self,
+        schema: SchemaKind,
+        config: FormatConfig,
+        num_rows: usize,
+        dir: &DatasetDir,
+        stage: &str,
+        offsets: &[u64],
+        cells: &mut Vec<(String, String)>,
+    ) -> Result<u64> {
+        let uri = dir.uri();
+        let opened = self.rt.block_on(dataset::open(uri))?;
+        let live = dataset::live_data_bytes(&opened, dir.path())?;
+        let usage = disk_usage(dir.path())?;
+        cells.push((
+            format!("{} fragments", stage),
+            opened.get_fragments().len().to_string(),
+        ));
+        cells.push((format!("{} live data bytes", stage), live.to_string()));
+        cells.push((
+            format!("{} bytes on disk", stage),
+            usage.total().to_string(),
+        ));
+
+        let (scan, _) = self.time(|| self.rt.block_on(dataset::scan(uri, &[])))?;
+        let (take, _) = self.time(|| self.rt.block_on(dataset::take(uri, offsets, &[])))?;
+        for (step, measure, timing) in [
+            ("compaction-scan", "scan", scan),
+            ("compaction-take", "take", take),
+        ] {
+            self.records.push(Record {
+                step: step.to_string(),
+                stage: Some(stage.to_string()),
+                file_bytes: Some(usage.total()),
+                column_bytes: Some(live),
+                latency_ms: Some(timing.median_ms),
+                timing: Some(timing),
+                ..Record::new(schema, config, num_rows)
+            });
+            cells.push((
+                format!("{} {} ms", stage, measure),
+                noisy_mark(format!("{:.2}", timing.median_ms), &timing),
+            ));
+        }
+        Ok(live)
+    }
+}
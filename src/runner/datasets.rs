@@ -0,0 +1,217 @@
+use super::{noisy_mark, Runner};
+use crate::data::SchemaKind;
+use crate::dataset::{self, disk_usage, row_ids, DatasetDir, DatasetOptions};
+use crate::error::Result;
+use crate::format::{FormatConfig, LanceCompression};
+use crate::projection::ColumnSelection;
+use crate::results::Record;
+use crate::stats::TimingStats;
+use ::lance::dataset::WriteMode;
+use arrow_array::RecordBatch;
+use lance_encoding::version::LanceFileVersion;
+use std::io::Write;
+
+// Reads of a Lance dataset. Both takes fetch the same rows, by offset and
+// by row id.
+#[derive(Debug, Clone, Copy)]
+enum DatasetRead {
+    Scan,
+    Take,
+    TakeRows,
+}
+
+impl DatasetRead {
+    const ALL: [DatasetRead; 3] = [DatasetRead::Scan, DatasetRead::Take, DatasetRead::TakeRows];
+
+    // (step of its records, measure in the report)
+    fn names(self) -> (&'static str, &'static str) {
+        match self {
+            DatasetRead::Scan => ("dataset-scan", "scan"),
+            DatasetRead::Take => ("dataset-take", "take"),
+            DatasetRead::TakeRows => ("dataset-take-rows", "take_rows"),
+        }
+    }
+}
+
+impl<W: Write> Runner<W> {
+    // Lance datasets rather than bare files: writes, bytes on disk split into
+    // data and manifests, and reads that open the dataset each time
+    pub fn datasets(
+        &mut self,
+        options: DatasetOptions,
+        selections: &[ColumnSelection],
+        indices: &[usize],
+    ) -> Result<()> {
+        let configs = self.lance_configs()?;
+        writeln!(self.out, "\n=== Dataset Benchmark ===")?;
+        writeln!(
+            self.out,
+            "\nmax_rows_per_file {}, max_rows_per_group {}; reads include opening the dataset",
+            options.max_rows_per_file, options.max_rows_per_group
+        )?;
+        let names: Vec<String> = configs.iter().map(|(c, _, _)| c.name()).collect();
+
+        self.each_table(
+            |_| Ok(()),
+            |runner, schema, batch| {
+                runner.dataset_table(
+                    schema, batch, &configs, &names, options, selections, indices,
+                )
+            },
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn dataset_table(
+        &mut self,
+        schema: SchemaKind,
+        batch: &RecordBatch,
+        configs: &[(FormatConfig, LanceFileVersion, LanceCompression)],
+        names: &[String],
+        options: DatasetOptions,
+        selections: &[ColumnSelection],
+        indices: &[usize],
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let offsets: Vec<u64> = if indices.is_empty() {
+            vec![num_rows as u64 / 2]
+        } else {
+            indices.iter().map(|&i| i as u64).collect()
+        };
+
+        let mut per_config = Vec::new();
+        for &(config, version, compression) in configs {
+            let mut cells = Vec::new();
+            if let Err(error) = self.dataset_cells(
+                schema,
+                config,
+                (version, compression.as_str()),
+                batch,
+                options,
+                selections,
+                &offsets,
+                &mut cells,
+            ) {
+                self.record(schema, config, num_rows, "dataset", error);
+            }
+            per_config.push(cells);
+        }
+
+        writeln!(self.out, "\n{} rows", num_rows)?;
+        self.measure_table(names, &per_config)?;
+        Ok(())
+    }
+
+    // Measure one configuration, pushing `(measure, cell)` pairs as it goes
+    // so a failure part way still reports what was measured
+    #[allow(clippy::too_many_arguments)]
+    fn dataset_cells(
+        &mut self,
+        schema: SchemaKind,
+        config: FormatConfig,
+        (version, compression): (LanceFileVersion, &str),
+        batch: &RecordBatch,
+        options: DatasetOptions,
+        selections: &[ColumnSelection],
+        offsets: &[u64],
+        cells: &mut Vec<(String, String)>,
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let batches = [batch.clone()];
+
+        // Every repetition writes a fresh dataset. Each is deleted when the
+        // next one has been timed, so only the last stays on disk.
+        let (timing, dir) = self.time(|| {
+            let dir = DatasetDir::new()?;
+            self.rt.block_on(dataset::write(
+                &batches,
+                dir.uri(),
+                version,
+                compression,
+                options,
+                WriteMode::Create,
+            ))?;
+            Ok(dir)
+        })?;
+        let usage = disk_usage(dir.path())?;
+        self.records.push(Record {
+            step: "dataset-write".to_string(),
+            file_bytes: Some(usage.total()),
+            latency_ms: Some(timing.median_ms),
+            timing: Some(timing),
+            ..Record::new(schema, config, num_rows)
+        });
+        cells.push((
+            "write ms".to_string(),
+            noisy_mark(format!("{:.2}", timing.median_ms), &timing),
+        ));
+        cells.push(("data bytes".to_string(), usage.data.to_string()));
+        cells.push((
+            "manifest + other bytes".to_string(),
+            format!(
+                "{} ({:.1}%)",
+                usage.metadata(),
+                usage.metadata() as f64 * 100.0 / usage.total().max(1) as f64
+            ),
+        ));
+        cells.push(("files".to_string(), usage.files.to_string()));
+
+        let uri = dir.uri().to_string();
+        let (timing, opened) = self.time(|| self.rt.block_on(dataset::open(&uri)))?;
+        let ids = row_ids(&opened, offsets)?;
+        self.push_timing(schema, config, num_rows, "dataset-open", None, timing);
+        cells.push((
+            "open ms".to_string(),
+            noisy_mark(format!("{:.2}", timing.median_ms), &timing),
+        ));
+
+        for selection in selections {
+            let columns = match selection {
+                // The scanner's own default reads everything
+                ColumnSelection::All => Vec::new(),
+                _ => selection.column_paths(&batch.schema()),
+            };
+            let label = selection.label();
+            for read in DatasetRead::ALL {
+                let (step, measure) = read.names();
+                let (timing, _) = self.time(|| match read {
+                    DatasetRead::Scan => {
+                        self.rt.block_on(dataset::scan(&uri, &columns)).map(|_| ())
+                    }
+                    DatasetRead::Take => self
+                        .rt
+                        .block_on(dataset::take(&uri, offsets, &columns))
+                        .map(|_| ()),
+                    DatasetRead::TakeRows => self
+                        .rt
+                        .block_on(dataset::take_rows(&uri, &ids, &columns))
+                        .map(|_| ()),
+                })?;
+                self.push_timing(schema, config, num_rows, step, Some(label.clone()), timing);
+                cells.push((
+                    format!("{} {} ms", measure, label),
+                    noisy_mark(format!("{:.2}", timing.median_ms), &timing),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn push_timing(
+        &mut self,
+        schema: SchemaKind,
+        config: FormatConfig,
+        rows: usize,
+        step: &str,
+        columns: Option<String>,
+        timing: TimingStats,
+    ) {
+        self.records.push(Record {
+            step: step.to_string(),
+            columns,
+            latency_ms: Some(timing.median_ms),
+            timing: Some(timing),
+            ..Record::new(schema, config, rows)
+        });
+    }
+}
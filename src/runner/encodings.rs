@@ -0,0 +1,143 @@
+use super::Runner;
+use crate::data::SchemaKind;
+use crate::error::Result;
+use crate::inspect::{encodings, ChunkEncoding};
+use crate::projection::ColumnSelection;
+use crate::results::Record;
+use arrow_array::RecordBatch;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+impl<W: Write> Runner<W> {
+    // Which encodings each configuration actually wrote: a tally over all
+    // columns, then every chunk of the selected columns. `tree` adds Lance's
+    // full encoding tree, or Parquet's per-page encoding stats.
+    pub fn encodings(&mut self, selections: &[ColumnSelection], tree: bool) -> Result<()> {
+        writeln!(self.out, "\n=== Encodings ===")?;
+
+        self.each_table(
+            |_| Ok(()),
+            |runner, schema, batch| runner.encoding_table(schema, batch, selections, tree),
+        )
+    }
+
+    fn encoding_table(
+        &mut self,
+        schema: SchemaKind,
+        batch: &RecordBatch,
+        selections: &[ColumnSelection],
+        tree: bool,
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let configs = self.options.configs.clone();
+        let files = self.write_files(schema, batch)?;
+        let selected: Vec<String> = selections
+            .iter()
+            .flat_map(|s| s.column_paths(&batch.schema()))
+            .collect();
+
+        for (config, bytes) in configs.iter().copied().zip(&files) {
+            let Some(bytes) = bytes else {
+                continue;
+            };
+            let chunks = match self.rt.block_on(encodings(config, bytes)) {
+                Ok(chunks) => chunks,
+                Err(error) => {
+                    self.record(schema, config, num_rows, "encoding", error);
+                    continue;
+                }
+            };
+
+            let mut by_column: BTreeMap<&str, Vec<&ChunkEncoding>> = BTreeMap::new();
+            for chunk in &chunks {
+                by_column
+                    .entry(chunk.column.as_str())
+                    .or_default()
+                    .push(chunk);
+            }
+            for (column, chunks) in &by_column {
+                let mut signatures: Vec<String> = chunks.iter().map(|c| c.signature()).collect();
+                signatures.dedup();
+                self.records.push(Record {
+                    step: "encoding".to_string(),
+                    columns: Some(column.to_string()),
+                    file_bytes: Some(bytes.len() as u64),
+                    column_bytes: Some(chunks.iter().map(|c| chunk_bytes(c)).sum()),
+                    encoding: Some(signatures.join("; ")),
+                    ..Record::new(schema, config, num_rows)
+                });
+            }
+
+            writeln!(self.out, "\n#### {}, {} rows", config.name(), num_rows)?;
+            writeln!(
+                self.out,
+                "\n| Encoding | Columns | Chunks | Values | Bytes |"
+            )?;
+            writeln!(self.out, "|----------|---------|--------|--------|-------|")?;
+            // (columns, chunks, values, bytes) per distinct encoding
+            let mut tally: BTreeMap<String, (usize, usize, u64, u64)> = BTreeMap::new();
+            for chunks in by_column.values() {
+                let mut seen = Vec::new();
+                for chunk in chunks {
+                    let signature = chunk.signature();
+                    let entry = tally.entry(signature.clone()).or_default();
+                    if !seen.contains(&signature) {
+                        entry.0 += 1;
+                        seen.push(signature);
+                    }
+                    entry.1 += 1;
+                    entry.2 += chunk.num_values;
+                    entry.3 += chunk_bytes(chunk);
+                }
+            }
+            for (signature, (columns, chunks, values, bytes)) in &tally {
+                writeln!(
+                    self.out,
+                    "| {} | {} | {} | {} | {} |",
+                    signature, columns, chunks, values, bytes
+                )?;
+            }
+
+            writeln!(
+                self.out,
+                "\n| Column | Chunk | Values | Layout | Encodings | Compression | Buffer bytes |"
+            )?;
+            writeln!(
+                self.out,
+                "|--------|-------|--------|--------|-----------|-------------|--------------|"
+            )?;
+            let selected_chunks: Vec<&ChunkEncoding> = chunks
+                .iter()
+                .filter(|c| selected.contains(&c.column))
+                .collect();
+            for chunk in &selected_chunks {
+                let buffers: Vec<String> = chunk.buffer_sizes.iter().map(u64::to_string).collect();
+                writeln!(
+                    self.out,
+                    "| {} | {} | {} | {} | {} | {} | {} |",
+                    chunk.column,
+                    chunk.chunk,
+                    chunk.num_values,
+                    chunk.layout,
+                    chunk.encodings.join(" > "),
+                    chunk.compression.as_deref().unwrap_or("-"),
+                    buffers.join(" + ")
+                )?;
+            }
+            if tree {
+                for chunk in &selected_chunks {
+                    writeln!(
+                        self.out,
+                        "\n<details><summary>{} chunk {}</summary>\n\n```\n{}\n```\n\n</details>",
+                        chunk.column, chunk.chunk, chunk.detail
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn chunk_bytes(chunk: &ChunkEncoding) -> u64 {
+    chunk.buffer_sizes.iter().sum()
+}
@@ -0,0 +1,167 @@
+use super::{noisy_mark, Runner};
+use crate::data::{generate_feature_columns, SchemaKind, ValueDistribution};
+use crate::error::{Error, Result};
+use crate::format::FormatConfig;
+use crate::mutate::{MutableTable, Mutation};
+use crate::projection::ColumnSelection;
+use crate::results::Record;
+use arrow_array::RecordBatch;
+use std::io::Write;
+use std::time::Instant;
+
+impl<W: Write> Runner<W> {
+    // Add `count` feature columns to a written table: Lance writes only the
+    // new columns as extra data files, Parquet rewrites the whole file. Then
+    // as many old and new columns are read back.
+    pub fn schema_evolution(&mut self, count: usize, takes: usize) -> Result<()> {
+        if count == 0 {
+            return Err(Error::InvalidInput(
+                "schema evolution needs at least one column to add".to_string(),
+            ));
+        }
+        writeln!(self.out, "\n=== Schema Evolution ===")?;
+        writeln!(
+            self.out,
+            "\nAdding {} feature columns; the add is a single run, reads are median ms over {} run(s)",
+            count,
+            self.options.repetitions.max(1)
+        )?;
+        let names: Vec<String> = self.options.configs.iter().map(|c| c.name()).collect();
+
+        self.each_table(
+            |_| Ok(()),
+            |runner, schema, batch| runner.evolution_table(schema, batch, &names, count, takes),
+        )
+    }
+
+    fn evolution_table(
+        &mut self,
+        schema: SchemaKind,
+        batch: &RecordBatch,
+        names: &[String],
+        count: usize,
+        takes: usize,
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let added = generate_feature_columns(
+            num_rows,
+            count,
+            "new_feature",
+            self.options.seed + 1,
+            ValueDistribution::Mixed,
+        );
+        let reads = [
+            (
+                "old",
+                ColumnSelection::FirstK(count).column_paths(&batch.schema()),
+            ),
+            (
+                "new",
+                added
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|f| f.name().clone())
+                    .collect(),
+            ),
+        ];
+        let mutation = Mutation::AddColumns(added);
+        let indices: Vec<usize> = (0..takes.min(num_rows))
+            .map(|i| i * num_rows / takes.min(num_rows))
+            .collect();
+
+        let mut per_config = Vec::new();
+        for config in self.options.configs.clone() {
+            let mut cells = Vec::new();
+            if let Err(error) = self.evolution_cells(
+                schema, config, batch, &mutation, &reads, &indices, &mut cells,
+            ) {
+                self.record(schema, config, num_rows, "add-columns", error);
+            }
+            per_config.push(cells);
+        }
+
+        writeln!(self.out, "\n{} rows", num_rows)?;
+        // The nested schema has no top-level feature columns to match the
+        // new ones, so its old columns are the struct's children
+        if reads[0].1.iter().any(|column| column.contains('.')) {
+            writeln!(
+                self.out,
+                "\n**Note**: the old columns are children of a struct and the new ones are top-level, so their reads also differ in nesting."
+            )?;
+        }
+        self.measure_table(names, &per_config)?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn evolution_cells(
+        &mut self,
+        schema: SchemaKind,
+        config: FormatConfig,
+        batch: &RecordBatch,
+        mutation: &Mutation,
+        reads: &[(&str, Vec<String>)],
+        indices: &[usize],
+        cells: &mut Vec<(String, String)>,
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let logical = mutation.changed_bytes(batch)?;
+        let mut table = self.rt.block_on(MutableTable::create(config, batch))?;
+        let before = table.bytes()?;
+
+        let start = Instant::now();
+        self.rt.block_on(table.apply(mutation))?;
+        let add_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let written = table.written_since(before)?;
+        let after = table.bytes()?;
+
+        let record = Record {
+            stage: Some(mutation.label()),
+            file_bytes: Some(after),
+            ..Record::new(schema, config, num_rows)
+        };
+        self.records.push(Record {
+            step: "add-columns".to_string(),
+            bytes_written: Some(written),
+            logical_bytes: Some(logical),
+            latency_ms: Some(add_ms),
+            ..record.clone()
+        });
+        cells.push(("add ms".to_string(), format!("{:.2}", add_ms)));
+        cells.push((
+            "bytes written".to_string(),
+            format!(
+                "{} ({:.2}x the new data)",
+                written,
+                written as f64 / logical.max(1) as f64
+            ),
+        ));
+        cells.push((
+            "bytes before -> after".to_string(),
+            format!("{} -> {}", before, after),
+        ));
+
+        for (label, columns) in reads {
+            let (scan, _) = self.time(|| self.rt.block_on(table.scan(columns)))?;
+            let (take, _) = self.time(|| self.rt.block_on(table.take(indices, columns)))?;
+            for (step, measure, timing) in
+                [("evolve-scan", "scan", scan), ("evolve-take", "take", take)]
+            {
+                self.records.push(Record {
+                    step: step.to_string(),
+                    columns: Some(columns.join(",")),
+                    stage: Some(label.to_string()),
+                    latency_ms: Some(timing.median_ms),
+                    timing: Some(timing),
+                    ..record.clone()
+                });
+                cells.push((
+                    format!("{} {} columns ms", measure, label),
+                    noisy_mark(format!("{:.2}", timing.median_ms), &timing),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
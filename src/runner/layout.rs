@@ -0,0 +1,88 @@
+use super::Runner;
+use crate::data::SchemaKind;
+use crate::error::Result;
+use crate::inspect::{layout, FileLayout};
+use crate::results::Record;
+use arrow_array::RecordBatch;
+use std::io::Write;
+
+impl<W: Write> Runner<W> {
+    // Bytes per file section, absolute and as a share of the file. Sections
+    // differ between formats, so a cell is `-` where a format has none.
+    pub fn layout(&mut self) -> Result<()> {
+        writeln!(self.out, "\n=== File Layout ===")?;
+
+        self.each_table(
+            |_| Ok(()),
+            |runner, schema, batch| runner.layout_table(schema, batch),
+        )
+    }
+
+    fn layout_table(&mut self, schema: SchemaKind, batch: &RecordBatch) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let configs = self.options.configs.clone();
+        let files = self.write_files(schema, batch)?;
+
+        let mut layouts: Vec<Option<FileLayout>> = Vec::new();
+        for (config, bytes) in configs.iter().copied().zip(&files) {
+            let Some(bytes) = bytes else {
+                layouts.push(None);
+                continue;
+            };
+            match self.rt.block_on(layout(config, bytes)) {
+                Ok(layout) => {
+                    for (section, section_bytes) in &layout.sections {
+                        self.records.push(Record {
+                            step: "layout".to_string(),
+                            file_bytes: Some(layout.file_bytes),
+                            section: Some(section.to_string()),
+                            section_bytes: Some(*section_bytes),
+                            ..Record::new(schema, config, num_rows)
+                        });
+                    }
+                    layouts.push(Some(layout));
+                }
+                Err(error) => {
+                    self.record(schema, config, num_rows, "layout", error);
+                    layouts.push(None);
+                }
+            }
+        }
+
+        let mut sections: Vec<&str> = Vec::new();
+        for layout in layouts.iter().flatten() {
+            for (section, _) in &layout.sections {
+                if !sections.contains(section) {
+                    sections.push(section);
+                }
+            }
+        }
+
+        writeln!(self.out, "\n{} rows", num_rows)?;
+        self.header(&["Section"])?;
+        for section in sections.iter().copied().chain(["total"]) {
+            let cells: Vec<String> = layouts
+                .iter()
+                .map(|layout| {
+                    let Some(layout) = layout else {
+                        return "failed".to_string();
+                    };
+                    let bytes = if section == "total" {
+                        Some(layout.file_bytes)
+                    } else {
+                        layout
+                            .sections
+                            .iter()
+                            .find(|(name, _)| *name == section)
+                            .map(|(_, bytes)| *bytes)
+                    };
+                    bytes.map_or("-".to_string(), |bytes| {
+                        format!("{} ({:.1}%)", bytes, layout.share(bytes))
+                    })
+                })
+                .collect();
+            writeln!(self.out, "| {} | {} |", section, cells.join(" | "))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use super::{noisy_mark, Runner};
+use crate::data::SchemaKind;
+use crate::error::{Error, Result};
+use crate::format::FormatConfig;
+use crate::mutate::{MutableTable, Mutation};
+use crate::projection::{feature_columns, ColumnSelection};
+use crate::results::Record;
+use crate::size::batch_size;
+use arrow_array::RecordBatch;
+use std::io::Write;
+use std::time::Instant;
+
+impl<W: Write> Runner<W> {
+    // Append, delete and update in turn, reading after each. Lance makes new
+    // dataset versions, Parquet rewrites its file. Amplification is bytes
+    // written over the logical bytes changed; slowdown is against the reads
+    // of the freshly written table.
+    pub fn mutations(
+        &mut self,
+        append_rows: usize,
+        every: i64,
+        update: &ColumnSelection,
+    ) -> Result<()> {
+        // Deletes take `uuid % every = 0` and updates `uuid % every = 1`:
+        // 0 divides by zero and 1 leaves nothing to update once deleted
+        if every < 2 {
+            return Err(Error::InvalidInput(format!(
+                "--every must be at least 2, got {}",
+                every
+            )));
+        }
+        writeln!(self.out, "\n=== Mutation Workloads ===")?;
+
+        self.each_table(
+            |runner| {
+                writeln!(
+                    runner.out,
+                    "Reads are median ms over {} run(s) with the slowdown against the \
+                     initial table",
+                    runner.options.repetitions.max(1)
+                )?;
+                runner.header(&["Rows", "Stage", "Measure"])
+            },
+            |runner, schema, batch| {
+                runner.mutation_table(schema, batch, append_rows, every, update)
+            },
+        )
+    }
+
+    fn mutation_table(
+        &mut self,
+        schema: SchemaKind,
+        batch: &RecordBatch,
+        append_rows: usize,
+        every: i64,
+        update: &ColumnSelection,
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let configs = self.options.configs.clone();
+        // Rows are picked by `uuid`, so updates leave it alone even under `all`
+        let features = feature_columns(&batch.schema());
+        let mutations = [
+            Mutation::Append(schema.generate(append_rows, self.options.seed + 1)),
+            Mutation::Delete { every },
+            Mutation::Update {
+                every,
+                columns: update
+                    .column_paths(&batch.schema())
+                    .into_iter()
+                    .filter(|column| features.contains(column))
+                    .collect(),
+            },
+        ];
+        let stages: Vec<String> = ["initial".to_string()]
+            .into_iter()
+            .chain(mutations.iter().map(Mutation::label))
+            .collect();
+        // The logical table after each stage, to size each change
+        let mut tables = vec![batch.clone()];
+        let mut changed = Vec::new();
+        for mutation in &mutations {
+            let table = tables.last().unwrap();
+            changed.push(mutation.changed_bytes(table)?);
+            tables.push(mutation.apply(table)?);
+        }
+
+        const MEASURES: [&str; 5] = [
+            "bytes written",
+            "amplification",
+            "write ms",
+            "scan ms",
+            "take ms",
+        ];
+        // cells[stage][measure][config]
+        let mut cells = vec![vec![Vec::new(); MEASURES.len()]; stages.len()];
+        for config in configs.iter().copied() {
+            let column = self.mutation_cells(
+                schema, config, batch, &mutations, &changed, &tables, &stages,
+            );
+            for (stage, measures) in column.into_iter().enumerate() {
+                for (measure, cell) in measures.into_iter().enumerate() {
+                    cells[stage][measure].push(cell);
+                }
+            }
+        }
+
+        for (stage, measures) in stages.iter().zip(cells) {
+            for (measure, row) in MEASURES.iter().zip(measures) {
+                writeln!(
+                    self.out,
+                    "| {} | {} | {} | {} |",
+                    num_rows,
+                    stage,
+                    measure,
+                    row.join(" | ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    // Cells per stage and measure for one configuration. A failed stage
+    // fails every stage after it, as they build on its result.
+    #[allow(clippy::too_many_arguments)]
+    fn mutation_cells(
+        &mut self,
+        schema: SchemaKind,
+        config: FormatConfig,
+        batch: &RecordBatch,
+        mutations: &[Mutation],
+        changed: &[u64],
+        tables: &[RecordBatch],
+        stages: &[String],
+    ) -> Vec<Vec<String>> {
+        let num_rows = batch.num_rows();
+        let mut cells = Vec::new();
+        let mut baseline: Option<(f64, f64)> = None;
+        let mut table: Option<MutableTable> = None;
+
+        for (stage, label) in stages.iter().enumerate() {
+            // Sizes are taken outside the timed write
+            let written = match stage {
+                0 => {
+                    let start = Instant::now();
+                    let created = self.rt.block_on(MutableTable::create(config, batch));
+                    let write_ms = start.elapsed().as_secs_f64() * 1000.0;
+                    created.and_then(|created| {
+                        let bytes = created.bytes()?;
+                        table = Some(created);
+                        Ok((bytes, write_ms))
+                    })
+                }
+                _ => match table.as_mut() {
+                    Some(current) => current.bytes().and_then(|before| {
+                        let start = Instant::now();
+                        self.rt.block_on(current.apply(&mutations[stage - 1]))?;
+                        let write_ms = start.elapsed().as_secs_f64() * 1000.0;
+                        Ok((current.written_since(before)?, write_ms))
+                    }),
+                    // Already reported when the earlier stage failed
+                    None => {
+                        cells.push(vec!["failed".to_string(); 5]);
+                        continue;
+                    }
+                },
+            };
+
+            let record = Record {
+                stage: Some(label.clone()),
+                ..Record::new(schema, config, num_rows)
+            };
+            let result = written.and_then(|(written, write_ms)| {
+                let logical = match stage {
+                    0 => batch_size(batch)?.logical,
+                    _ => changed[stage - 1],
+                };
+                let current = table.as_ref().unwrap();
+                let rows = tables[stage].num_rows();
+                self.stage_cells(
+                    record,
+                    current,
+                    (written, logical, write_ms),
+                    rows,
+                    &mut baseline,
+                )
+            });
+            match result {
+                Ok(stage_cells) => cells.push(stage_cells),
+                Err(error) => {
+                    self.record(schema, config, num_rows, "mutate", error);
+                    table = None;
+                    cells.push(vec!["failed".to_string(); 5]);
+                }
+            }
+        }
+        cells
+    }
+
+    // Record one stage of a mutation workload and read the table after it.
+    // `baseline` holds the first stage's read latencies.
+    fn stage_cells(
+        &mut self,
+        record: Record,
+        table: &MutableTable,
+        (written, logical, write_ms): (u64, u64, f64),
+        rows: usize,
+        baseline: &mut Option<(f64, f64)>,
+    ) -> Result<Vec<String>> {
+        let indices: Vec<usize> = (0..10.min(rows)).map(|i| i * rows / 10.min(rows)).collect();
+        let (scan, _) = self.time(|| self.rt.block_on(table.scan(&[])))?;
+        let (take, _) = self.time(|| self.rt.block_on(table.take(&indices, &[])))?;
+        let (scan_base, take_base) = *baseline.get_or_insert((scan.median_ms, take.median_ms));
+
+        let record = Record {
+            file_bytes: Some(table.bytes()?),
+            ..record
+        };
+        self.records.push(Record {
+            step: "mutate".to_string(),
+            bytes_written: Some(written),
+            logical_bytes: Some(logical),
+            latency_ms: Some(write_ms),
+            ..record.clone()
+        });
+        for (step, timing) in [("scan", scan), ("take", take)] {
+            self.records.push(Record {
+                step: step.to_string(),
+                latency_ms: Some(timing.median_ms),
+                timing: Some(timing),
+                ..record.clone()
+            });
+        }
+
+        Ok(vec![
+            written.to_string(),
+            format!("{:.2}x", written as f64 / logical.max(1) as f64),
+            format!("{:.2}", write_ms),
+            noisy_mark(
+                format!("{:.2} ({:.2}x)", scan.median_ms, scan.median_ms / scan_base),
+                &scan,
+            ),
+            noisy_mark(
+                format!("{:.2} ({:.2}x)", take.median_ms, take.median_ms / take_base),
+                &take,
+            ),
+        ])
+    }
+}
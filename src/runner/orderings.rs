@@ -0,0 +1,123 @@
+use super::{ci_cell, noisy_mark, ReadOp, Runner};
+use crate::data::SchemaKind;
+use crate::error::Result;
+use crate::ordering::{reorder, RowOrder};
+use crate::results::Record;
+use crate::size::batch_size;
+use arrow_array::RecordBatch;
+use std::io::Write;
+
+impl<W: Write> Runner<W> {
+    // Size, scan and take cost per row order. Takes fetch the same original
+    // rows under every order, at their remapped positions; empty `indices`
+    // means ten rows spread evenly over the batch.
+    pub fn orderings(&mut self, orders: &[RowOrder], indices: &[usize]) -> Result<()> {
+        writeln!(self.out, "\n=== Row Ordering ===")?;
+
+        self.each_table(
+            |runner| {
+                writeln!(
+                    runner.out,
+                    "Cells show file bytes (ratio), or median ms [95% CI] over {} run(s)",
+                    runner.options.repetitions.max(1)
+                )?;
+                runner.header(&["Rows", "Order", "Measure"])
+            },
+            |runner, schema, batch| runner.ordering_table(schema, batch, orders, indices),
+        )
+    }
+
+    fn ordering_table(
+        &mut self,
+        schema: SchemaKind,
+        batch: &RecordBatch,
+        orders: &[RowOrder],
+        indices: &[usize],
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let configs = self.options.configs.clone();
+        let memory = batch_size(batch)?;
+        let original: Vec<usize> = if indices.is_empty() {
+            (0..10).map(|i| i * num_rows / 10).collect()
+        } else {
+            indices.to_vec()
+        };
+
+        for order in orders {
+            let reordered = reorder(batch, order)?;
+            let take_indices = reordered.remap(&original)?;
+            let label = order.label();
+            // Everything measured under this order is tagged with it
+            // at the end, failures included
+            let first_record = self.records.len();
+            let files = self.write_files(schema, &reordered.batch)?;
+
+            let mut sizes = Vec::new();
+            let mut scans = Vec::new();
+            let mut takes = Vec::new();
+            for (config, bytes) in configs.iter().copied().zip(&files) {
+                let Some(bytes) = bytes else {
+                    for cells in [&mut sizes, &mut scans, &mut takes] {
+                        cells.push("failed".to_string());
+                    }
+                    continue;
+                };
+                let ratio = memory.logical as f64 / bytes.len() as f64;
+                self.records.push(Record {
+                    step: "compress".to_string(),
+                    file_bytes: Some(bytes.len() as u64),
+                    ratio: Some(ratio),
+                    logical_bytes: Some(memory.logical),
+                    physical_bytes: Some(memory.physical),
+                    ..Record::new(schema, config, num_rows)
+                });
+                sizes.push(format!("{} ({:.2}x)", bytes.len(), ratio));
+
+                for (op, cells) in [
+                    (ReadOp::Scan, &mut scans),
+                    (ReadOp::Take(&take_indices), &mut takes),
+                ] {
+                    let step = op.step();
+                    let result = self.time(|| match op {
+                        ReadOp::Scan => self.rt.block_on(config.read(bytes)).map(|_| ()),
+                        ReadOp::Take(indices) => {
+                            self.rt.block_on(config.take(bytes, indices)).map(|_| ())
+                        }
+                    });
+                    match result {
+                        Ok((timing, ())) => {
+                            self.records.push(Record {
+                                step: step.to_string(),
+                                file_bytes: Some(bytes.len() as u64),
+                                latency_ms: Some(timing.median_ms),
+                                timing: Some(timing),
+                                ..Record::new(schema, config, num_rows)
+                            });
+                            let cell = format!("{:.2} {}", timing.median_ms, ci_cell(&timing, 2));
+                            cells.push(noisy_mark(cell, &timing));
+                        }
+                        Err(error) => {
+                            self.record(schema, config, num_rows, step, error);
+                            cells.push("failed".to_string());
+                        }
+                    }
+                }
+            }
+            for record in &mut self.records[first_record..] {
+                record.ordering = Some(label.clone());
+            }
+
+            for (measure, cells) in [("bytes", sizes), ("scan ms", scans), ("take ms", takes)] {
+                writeln!(
+                    self.out,
+                    "| {} | {} | {} | {} |",
+                    num_rows,
+                    label,
+                    measure,
+                    cells.join(" | ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
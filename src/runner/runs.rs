@@ -0,0 +1,192 @@
+use super::Runner;
+use crate::data::SchemaKind;
+use crate::error::Result;
+use crate::inspect::column_sizes;
+use crate::projection::ColumnSelection;
+use crate::results::Record;
+use crate::runs::{analyze, RunStats, SizeEstimates};
+use arrow_array::RecordBatch;
+use std::collections::HashMap;
+use std::io::Write;
+
+impl<W: Write> Runner<W> {
+    // Run statistics and estimated sizes under each encoding, against what
+    // the formats actually wrote. Totals cover every column; the selected
+    // columns are also listed one by one.
+    pub fn runs(&mut self, selections: &[ColumnSelection]) -> Result<()> {
+        writeln!(self.out, "\n=== Run Lengths and Size Estimates ===")?;
+
+        self.each_table(
+            |_| Ok(()),
+            |runner, schema, batch| runner.run_table(schema, batch, selections),
+        )
+    }
+
+    fn run_table(
+        &mut self,
+        schema: SchemaKind,
+        batch: &RecordBatch,
+        selections: &[ColumnSelection],
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let configs = self.options.configs.clone();
+        let stats = analyze(batch)?;
+        let files = self.write_files(schema, batch)?;
+
+        // Actual compressed bytes per column, per configuration
+        let mut actual: Vec<Option<HashMap<String, u64>>> = Vec::new();
+        for (config, bytes) in configs.iter().copied().zip(&files) {
+            let Some(bytes) = bytes else {
+                actual.push(None);
+                continue;
+            };
+            match self.rt.block_on(column_sizes(config, bytes)) {
+                Ok(sizes) => actual.push(Some(
+                    sizes
+                        .columns
+                        .into_iter()
+                        .map(|c| (c.column, c.compressed_bytes))
+                        .collect(),
+                )),
+                Err(error) => {
+                    self.record(schema, config, num_rows, "column", error);
+                    actual.push(None);
+                }
+            }
+        }
+
+        for column in &stats {
+            let (best, bytes) = column.estimates().best();
+            self.records.push(Record {
+                step: "estimate".to_string(),
+                config: "estimate".to_string(),
+                schema: schema.name().to_string(),
+                rows: num_rows as u64,
+                columns: Some(column.column.clone()),
+                column_bytes: Some(bytes),
+                encoding: Some(best.to_string()),
+                ..Record::default()
+            });
+        }
+
+        writeln!(
+            self.out,
+            "\n{} rows, run statistics over all columns",
+            num_rows
+        )?;
+        writeln!(self.out, "\n| Statistic | Min | Median | Max |")?;
+        writeln!(self.out, "|-----------|-----|--------|-----|")?;
+        let statistics: [(&str, fn(&RunStats) -> f64); 4] = [
+            ("runs", |s| s.runs as f64),
+            ("mean run", |s| s.mean_run()),
+            ("distinct", |s| s.distinct as f64),
+            ("entropy (bits)", |s| s.entropy),
+        ];
+        for (name, statistic) in statistics {
+            let mut values: Vec<f64> = stats.iter().map(statistic).collect();
+            if values.is_empty() {
+                continue;
+            }
+            values.sort_by(f64::total_cmp);
+            writeln!(
+                self.out,
+                "| {} | {:.2} | {:.2} | {:.2} |",
+                name,
+                values[0],
+                values[values.len() / 2],
+                values[values.len() - 1]
+            )?;
+        }
+
+        // The best estimate picks each column's cheapest encoding
+        let totals = |pick: &dyn Fn(&SizeEstimates) -> u64| -> u64 {
+            stats.iter().map(|s| pick(&s.estimates())).sum()
+        };
+        let best = totals(&|e| e.best().1);
+        writeln!(self.out, "\n{} rows, column data bytes", num_rows)?;
+        writeln!(self.out, "\n| Size | Bytes | vs best estimate |")?;
+        writeln!(self.out, "|------|-------|------------------|")?;
+        let mut rows: Vec<(String, Option<u64>)> = SizeEstimates::NAMES
+            .into_iter()
+            .map(|name| {
+                (
+                    format!("estimate: {}", name),
+                    Some(totals(&|e| e.get(name))),
+                )
+            })
+            .collect();
+        rows.push(("estimate: best per column".to_string(), Some(best)));
+        rows.push(("entropy bound".to_string(), Some(totals(&|e| e.entropy))));
+        for (config, sizes) in configs.iter().zip(&actual) {
+            rows.push((
+                config.name(),
+                sizes.as_ref().map(|sizes| sizes.values().sum()),
+            ));
+        }
+        for (name, bytes) in rows {
+            match bytes {
+                Some(bytes) => writeln!(
+                    self.out,
+                    "| {} | {} | {:.2}x |",
+                    name,
+                    bytes,
+                    bytes as f64 / best.max(1) as f64
+                )?,
+                None => writeln!(self.out, "| {} | failed | - |", name)?,
+            }
+        }
+
+        let selected: Vec<String> = selections
+            .iter()
+            .flat_map(|s| s.column_paths(&batch.schema()))
+            .collect();
+        if selected.is_empty() {
+            return Ok(());
+        }
+        writeln!(self.out, "\n{} rows, selected columns", num_rows)?;
+        self.header(&[
+            "Column",
+            "Runs",
+            "Mean run",
+            "Max run",
+            "Distinct",
+            "Entropy",
+            "Plain",
+            "RLE",
+            "Bitpacking",
+            "Dictionary",
+            "Best",
+        ])?;
+        for column in stats.iter().filter(|s| selected.contains(&s.column)) {
+            let estimates = column.estimates();
+            let (best, bytes) = estimates.best();
+            let cells: Vec<String> = actual
+                .iter()
+                .map(|sizes| match sizes {
+                    Some(sizes) => sizes
+                        .get(&column.column)
+                        .map_or("-".to_string(), u64::to_string),
+                    None => "failed".to_string(),
+                })
+                .collect();
+            writeln!(
+                self.out,
+                "| {} | {} | {:.2} | {} | {} | {:.2} | {} | {} | {} | {} | {} ({}) | {} |",
+                column.column,
+                column.runs,
+                column.mean_run(),
+                column.max_run,
+                column.distinct,
+                column.entropy,
+                estimates.plain,
+                estimates.rle,
+                estimates.bitpacking,
+                estimates.dictionary,
+                bytes,
+                best,
+                cells.join(" | ")
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use super::{noisy_mark, ReadOp, Runner};
+use crate::data::SchemaKind;
+use crate::error::Result;
+use crate::format::FormatConfig;
+use crate::projection::ColumnSelection;
+use crate::results::Record;
+use crate::split::{SplitKind, SplitTable};
+use arrow_array::RecordBatch;
+use std::io::Write;
+
+impl<W: Write> Runner<W> {
+    // The same table cut into more and more pieces: Lance fragments, Parquet
+    // files and Parquet row groups. Takes fetch `takes` rows spread evenly
+    // over the whole table, so every piece gets visited.
+    pub fn scaling(
+        &mut self,
+        pieces: &[usize],
+        selections: &[ColumnSelection],
+        takes: usize,
+    ) -> Result<()> {
+        writeln!(self.out, "\n=== Split Scaling ===")?;
+        let mut layouts = Vec::new();
+        for config in self.options.configs.clone() {
+            match config {
+                FormatConfig::Lance { .. } => layouts.push((config, SplitKind::Files, "fragments")),
+                FormatConfig::Parquet { .. } => {
+                    layouts.push((config, SplitKind::Files, "files"));
+                    layouts.push((config, SplitKind::RowGroups, "row-groups"));
+                }
+            }
+        }
+        let names: Vec<String> = layouts
+            .iter()
+            .map(|(config, _, label)| format!("{} {}", config.name(), label))
+            .collect();
+
+        self.each_table(
+            |runner| {
+                writeln!(
+                    runner.out,
+                    "Cells show bytes on disk, or median ms over {} run(s), `~` marks noisy timings; every read opens its pieces from local disk",
+                    runner.options.repetitions.max(1)
+                )?;
+                runner.header_for(&["Rows", "Pieces", "Read"], &names)
+            },
+            |runner, schema, batch| {
+                runner.scaling_table(schema, batch, &layouts, pieces, selections, takes)
+            },
+        )
+    }
+
+    fn scaling_table(
+        &mut self,
+        schema: SchemaKind,
+        batch: &RecordBatch,
+        layouts: &[(FormatConfig, SplitKind, &str)],
+        pieces: &[usize],
+        selections: &[ColumnSelection],
+        takes: usize,
+    ) -> Result<()> {
+        let num_rows = batch.num_rows();
+        let indices: Vec<usize> = (0..takes.min(num_rows))
+            .map(|i| i * num_rows / takes.min(num_rows))
+            .collect();
+        let reads: Vec<(String, Vec<String>)> = selections
+            .iter()
+            .map(|selection| {
+                let columns = match selection {
+                    ColumnSelection::All => Vec::new(),
+                    _ => selection.column_paths(&batch.schema()),
+                };
+                (selection.label(), columns)
+            })
+            .collect();
+
+        for &count in pieces {
+            // One row of cells per read, the byte count first
+            let mut rows: Vec<(String, Vec<String>)> = vec![("bytes".to_string(), Vec::new())];
+            for (label, _) in &reads {
+                rows.push((format!("scan {}", label), Vec::new()));
+                rows.push((format!("take {}", label), Vec::new()));
+            }
+
+            for &(config, kind, layout) in layouts {
+                let record = Record {
+                    layout: Some(layout.to_string()),
+                    pieces: Some(count as u64),
+                    ..Record::new(schema, config, num_rows)
+                };
+                let written = self
+                    .rt
+                    .block_on(SplitTable::write(config, batch, count, kind))
+                    .and_then(|table| Ok((table.bytes()?, table)));
+                let (bytes, table) = match written {
+                    Ok(written) => written,
+                    Err(error) => {
+                        self.record_failure(record.clone(), "split-write", error);
+                        for (_, cells) in &mut rows {
+                            cells.push("failed".to_string());
+                        }
+                        continue;
+                    }
+                };
+                // Not `compress`: the bytes of a split table are
+                // not comparable with those of one file
+                self.records.push(Record {
+                    step: "split-write".to_string(),
+                    file_bytes: Some(bytes),
+                    ..record.clone()
+                });
+                rows[0].1.push(bytes.to_string());
+
+                for (i, (label, columns)) in reads.iter().enumerate() {
+                    for (j, op) in [ReadOp::Scan, ReadOp::Take(&indices)]
+                        .into_iter()
+                        .enumerate()
+                    {
+                        let step = op.step();
+                        let result = self.time(|| match op {
+                            ReadOp::Scan => self.rt.block_on(table.scan(columns)),
+                            ReadOp::Take(indices) => self.rt.block_on(table.take(indices, columns)),
+                        });
+                        let cell = match result {
+                            Ok((timing, _)) => {
+                                self.records.push(Record {
+                                    step: step.to_string(),
+                                    columns: Some(label.clone()),
+                                    file_bytes: Some(bytes),
+                                    latency_ms: Some(timing.median_ms),
+                                    timing: Some(timing),
+                                    ..record.clone()
+                                });
+                                noisy_mark(format!("{:.2}", timing.median_ms), &timing)
+                            }
+                            Err(error) => {
+                                let failed = Record {
+                                    columns: Some(label.clone()),
+                                    ..record.clone()
+                                };
+                                self.record_failure(failed, step, error);
+                                "failed".to_string()
+                            }
+                        };
+                        rows[1 + i * 2 + j].1.push(cell);
+                    }
+                }
+            }
+
+            for (read, cells) in rows {
+                writeln!(
+                    self.out,
+                    "| {} | {} | {} | {} |",
+                    num_rows,
+                    count,
+                    read,
+                    cells.join(" | ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use crate::error::{Error, Result};
+use crate::projection::leaf_arrays;
+use arrow::array::AsArray;
+use arrow::datatypes::{Float32Type, Float64Type, Int32Type, Int64Type};
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_schema::DataType;
+use std::collections::HashMap;
+
+// Lance's RLE writes each run length as a u8 (see `RleMiniBlockEncoder` in
+// lance-encoding at the pinned rev), so longer runs are split
+const MAX_RUN: u64 = 255;
+// Bitpacking picks a bit width per chunk of this many values
+const BITPACK_CHUNK: usize = 1024;
+
+// Run and value statistics of one leaf column. Nulls are skipped, they are
+// paid for by the validity bitmap whatever the encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunStats {
+    pub column: String,
+    pub values: u64,
+    pub nulls: u64,
+    // Bits per value in the plain encoding: 1 for booleans, which are
+    // bitmaps, and the width of the type otherwise
+    pub value_bits: u64,
+    pub runs: u64,
+    // Runs after splitting at `MAX_RUN`
+    pub rle_runs: u64,
+    pub max_run: u64,
+    pub distinct: u64,
+    // Shannon entropy in bits per value
+    pub entropy: f64,
+    // Sum of per-chunk bit widths times chunk lengths, in bits
+    pub bitpacked_bits: u64,
+    pub bitpacked_chunks: u64,
+}
+
+impl RunStats {
+    pub fn mean_run(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.values as f64 / self.runs as f64
+        }
+    }
+
+    fn validity_bytes(&self) -> u64 {
+        if self.nulls == 0 {
+            0
+        } else {
+            (self.values + self.nulls).div_ceil(8)
+        }
+    }
+
+    // Expected bytes under simple models of each encoding, without headers
+    // beyond a byte per bitpacked chunk for its width. RLE stores every run
+    // value in whole bytes next to its length byte.
+    pub fn estimates(&self) -> SizeEstimates {
+        let validity = self.validity_bytes();
+        let index_bits = u64::from(64 - self.distinct.saturating_sub(1).leading_zeros());
+        SizeEstimates {
+            plain: validity + (self.values * self.value_bits).div_ceil(8),
+            rle: validity + self.rle_runs * (self.value_bits.div_ceil(8) + 1),
+            bitpacking: validity + self.bitpacked_bits.div_ceil(8) + self.bitpacked_chunks,
+            dictionary: validity
+                + (self.distinct * self.value_bits).div_ceil(8)
+                + (self.values * index_bits).div_ceil(8),
+            entropy: validity + (self.values as f64 * self.entropy / 8.0).ceil() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeEstimates {
+    pub plain: u64,
+    pub rle: u64,
+    pub bitpacking: u64,
+    pub dictionary: u64,
+    // The entropy bound: no encoding of independent values does better
+    pub entropy: u64,
+}
+
+impl SizeEstimates {
+    pub const NAMES: [&'static str; 4] = ["plain", "rle", "bitpacking", "dictionary"];
+
+    pub fn get(&self, name: &str) -> u64 {
+        match name {
+            "plain" => self.plain,
+            "rle" => self.rle,
+            "bitpacking" => self.bitpacking,
+            "dictionary" => self.dictionary,
+            _ => self.entropy,
+        }
+    }
+
+    // The smallest of the real encodings, by name
+    pub fn best(&self) -> (&'static str, u64) {
+        Self::NAMES
+            .into_iter()
+            .map(|name| (name, self.get(name)))
+            .min_by_key(|(_, bytes)| *bytes)
+            .unwrap()
+    }
+}
+
+// Run statistics for every leaf column of `batch`, struct children expanded
+// and named like `projection::leaf_columns`
+pub fn analyze(batch: &RecordBatch) -> Result<Vec<RunStats>> {
+    leaf_arrays(batch)
+        .into_iter()
+        .map(|(name, _, array)| analyze_column(name, &array))
+        .collect()
+}
+
+// The raw bits of each value, so equal values compare equal and the bit
+// width is what a bitpacker working on those bits would see
+fn value_bits(array: &ArrayRef) -> Result<(Vec<Option<u64>>, u64)> {
+    let bits = match array.data_type() {
+        DataType::Float64 => {
+            let values = array.as_primitive::<Float64Type>();
+            (values.iter().map(|v| v.map(f64::to_bits)).collect(), 64)
+        }
+        DataType::Float32 => {
+            let values = array.as_primitive::<Float32Type>();
+            (
+                values
+                    .iter()
+                    .map(|v| v.map(|v| u64::from(v.to_bits())))
+                    .collect(),
+                32,
+            )
+        }
+        DataType::Int64 => {
+            let values = array.as_primitive::<Int64Type>();
+            (values.iter().map(|v| v.map(|v| v as u64)).collect(), 64)
+        }
+        DataType::Int32 => {
+            let values = array.as_primitive::<Int32Type>();
+            (
+                values
+                    .iter()
+                    .map(|v| v.map(|v| u64::from(v as u32)))
+                    .collect(),
+                32,
+            )
+        }
+        DataType::Boolean => {
+            let values = array.as_boolean();
+            (values.iter().map(|v| v.map(u64::from)).collect(), 1)
+        }
+        other => {
+            return Err(Error::InvalidInput(format!(
+                "run analysis doesn't support {} columns",
+                other
+            )))
+        }
+    };
+    Ok(bits)
+}
+
+fn analyze_column(column: String, array: &ArrayRef) -> Result<RunStats> {
+    let (bits, width) = value_bits(array)?;
+    let values: Vec<u64> = bits.iter().flatten().copied().collect();
+
+    let mut runs = 0;
+    let mut rle_runs = 0;
+    let mut max_run = 0;
+    let mut run = 0;
+    for (i, value) in values.iter().enumerate() {
+        run += 1;
+        if values.get(i + 1) != Some(value) {
+            runs += 1;
+            rle_runs += run.div_ceil(MAX_RUN);
+            max_run = max_run.max(run);
+            run = 0;
+        }
+    }
+
+    let mut counts: HashMap<u64, u64> = HashMap::new();
+    for value in &values {
+        *counts.entry(*value).or_default() += 1;
+    }
+    let total = values.len() as f64;
+    let entropy: f64 = counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum();
+
+    let mut bitpacked_bits = 0;
+    let mut bitpacked_chunks = 0;
+    for chunk in values.chunks(BITPACK_CHUNK) {
+        let width = 64 - chunk.iter().fold(0, |acc, v| acc | v).leading_zeros();
+        bitpacked_bits += u64::from(width) * chunk.len() as u64;
+        bitpacked_chunks += 1;
+    }
+
+    Ok(RunStats {
+        column,
+        values: values.len() as u64,
+        nulls: array.null_count() as u64,
+        value_bits: width,
+        runs,
+        rle_runs,
+        max_run,
+        distinct: counts.len() as u64,
+        entropy,
+        bitpacked_bits,
+        bitpacked_chunks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{BooleanArray, Int64Array};
+    use std::sync::Arc;
+
+    fn stats(array: impl Array + 'static) -> RunStats {
+        analyze_column("c".to_string(), &(Arc::new(array) as ArrayRef)).unwrap()
+    }
+
+    #[test]
+    fn counts_runs() {
+        let stats = stats(Int64Array::from(vec![1, 1, 1, 2, 2, 1]));
+        assert_eq!(stats.runs, 3);
+        assert_eq!(stats.rle_runs, 3);
+        assert_eq!(stats.max_run, 3);
+        assert_eq!(stats.distinct, 2);
+        assert_eq!(stats.mean_run(), 2.0);
+    }
+
+    #[test]
+    fn splits_runs_longer_than_max_run() {
+        let stats = stats(Int64Array::from(vec![7; MAX_RUN as usize * 2 + 1]));
+        assert_eq!(stats.runs, 1);
+        assert_eq!(stats.rle_runs, 3);
+        assert_eq!(stats.max_run, MAX_RUN * 2 + 1);
+        assert_eq!(stats.estimates().rle, 3 * (8 + 1));
+    }
+
+    #[test]
+    fn skips_nulls_and_charges_validity() {
+        let stats = stats(Int64Array::from(vec![Some(4), None, Some(4), None]));
+        assert_eq!((stats.values, stats.nulls), (2, 2));
+        // The nulls don't break the run
+        assert_eq!(stats.runs, 1);
+        let estimates = stats.estimates();
+        assert_eq!(estimates.plain, 1 + 2 * 8);
+        assert_eq!(estimates.rle, 1 + (8 + 1));
+    }
+
+    #[test]
+    fn constant_column() {
+        let stats = stats(Int64Array::from(vec![5; 2048]));
+        assert_eq!(stats.entropy, 0.0);
+        assert_eq!(stats.distinct, 1);
+        assert_eq!(stats.bitpacked_chunks, 2);
+        // 5 needs 3 bits
+        assert_eq!(stats.bitpacked_bits, 3 * 2048);
+        let estimates = stats.estimates();
+        assert_eq!(estimates.entropy, 0);
+        // A single entry needs no index bits
+        assert_eq!(estimates.dictionary, 8);
+        assert_eq!(estimates.rle, 2048_u64.div_ceil(MAX_RUN) * 9);
+        assert_eq!(estimates.best().0, "dictionary");
+    }
+
+    #[test]
+    fn booleans_are_one_bit_per_value() {
+        let stats = stats(BooleanArray::from(vec![true, false].repeat(8)));
+        assert_eq!(stats.value_bits, 1);
+        assert_eq!(stats.runs, 16);
+        assert_eq!(stats.entropy, 1.0);
+        let estimates = stats.estimates();
+        assert_eq!(estimates.plain, 2);
+        assert_eq!(estimates.rle, 16 * 2);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::error::Result;
+use arrow_array::{Array, RecordBatch};
+
+// In-memory size of a batch, the baseline every compression ratio is taken
+// against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchSize {
+    // Bytes the rows actually cover: values, validity bitmaps and offsets,
+    // recursing into children
+    pub logical: u64,
+    // Bytes held by the batch's buffers, including unused capacity
+    pub physical: u64,
+}
+
+pub fn batch_size(batch: &RecordBatch) -> Result<BatchSize> {
+    let mut logical = 0;
+    for column in batch.columns() {
+        logical += column.to_data().get_slice_memory_size()? as u64;
+    }
+    Ok(BatchSize {
+        logical,
+        physical: batch.get_array_memory_size() as u64,
+    })
+}
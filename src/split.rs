@@ -0,0 +1,167 @@
+use crate::dataset::{self, DatasetDir, DatasetOptions};
+use crate::error::{Error, Result};
+use crate::format::FormatConfig;
+use crate::parquet;
+use ::lance::dataset::WriteMode;
+use arrow_array::RecordBatch;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// How a table is cut into pieces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitKind {
+    // Lance fragments, or separate Parquet files
+    Files,
+    // Row groups of a single Parquet file
+    RowGroups,
+}
+
+// One generated table written as `pieces` pieces, read back as one table:
+// scans cover every piece and take indices are global row numbers. Every
+// format goes to files on local disk, opened again by each read.
+#[derive(Debug)]
+pub enum SplitTable {
+    LanceFragments(DatasetDir),
+    // `starts[i]` is the global row number of the first row of `files[i]`
+    ParquetFiles {
+        dir: DatasetDir,
+        files: Vec<PathBuf>,
+        starts: Vec<usize>,
+    },
+    ParquetRowGroups {
+        dir: DatasetDir,
+        file: PathBuf,
+    },
+}
+
+impl SplitTable {
+    // Parquet splits either way, Lance only into fragments
+    pub async fn write(
+        config: FormatConfig,
+        batch: &RecordBatch,
+        pieces: usize,
+        kind: SplitKind,
+    ) -> Result<SplitTable> {
+        let rows_per_piece = batch.num_rows().div_ceil(pieces.max(1)).max(1);
+        let dir = DatasetDir::new()?;
+        let table = match (config, kind) {
+            (
+                FormatConfig::Lance {
+                    version,
+                    compression,
+                },
+                SplitKind::Files,
+            ) => {
+                let defaults = DatasetOptions::default();
+                let options = DatasetOptions {
+                    max_rows_per_file: rows_per_piece,
+                    max_rows_per_group: defaults.max_rows_per_group.min(rows_per_piece),
+                };
+                dataset::write(
+                    &[batch.clone()],
+                    dir.uri(),
+                    version,
+                    compression.as_str(),
+                    options,
+                    WriteMode::Create,
+                )
+                .await?;
+                SplitTable::LanceFragments(dir)
+            }
+            (FormatConfig::Lance { .. }, SplitKind::RowGroups) => {
+                return Err(Error::InvalidInput(
+                    "lance tables split into fragments, not row groups".to_string(),
+                ))
+            }
+            (FormatConfig::Parquet { compression }, SplitKind::Files) => {
+                fs::create_dir_all(dir.path())?;
+                let mut files = Vec::new();
+                let mut starts = Vec::new();
+                for start in (0..batch.num_rows()).step_by(rows_per_piece) {
+                    let length = rows_per_piece.min(batch.num_rows() - start);
+                    let piece = batch.slice(start, length);
+                    let path = dir.path().join(format!("{}.parquet", files.len()));
+                    fs::write(&path, parquet::write_bytes_with(piece, compression, None)?)?;
+                    files.push(path);
+                    starts.push(start);
+                }
+                SplitTable::ParquetFiles { dir, files, starts }
+            }
+            (FormatConfig::Parquet { compression }, SplitKind::RowGroups) => {
+                fs::create_dir_all(dir.path())?;
+                let file = dir.path().join("0.parquet");
+                let bytes = parquet::write_bytes_split(
+                    batch.clone(),
+                    compression,
+                    None,
+                    Some(rows_per_piece),
+                )?;
+                fs::write(&file, bytes)?;
+                SplitTable::ParquetRowGroups { dir, file }
+            }
+        };
+        Ok(table)
+    }
+
+    // Everything on disk, for Lance the manifests too
+    pub fn bytes(&self) -> Result<u64> {
+        let dir = match self {
+            SplitTable::LanceFragments(dir)
+            | SplitTable::ParquetFiles { dir, .. }
+            | SplitTable::ParquetRowGroups { dir, .. } => dir,
+        };
+        Ok(dataset::disk_usage(dir.path())?.total())
+    }
+
+    // Read `columns`, or everything if empty, and return the row count
+    pub async fn scan(&self, columns: &[String]) -> Result<usize> {
+        let read = |path: &Path| -> Result<usize> {
+            let batches = parquet::read_file(path, columns)?;
+            Ok(batches.iter().map(RecordBatch::num_rows).sum())
+        };
+        match self {
+            SplitTable::LanceFragments(dir) => dataset::scan(dir.uri(), columns).await,
+            SplitTable::ParquetFiles { files, .. } => {
+                let mut rows = 0;
+                for file in files {
+                    rows += read(file)?;
+                }
+                Ok(rows)
+            }
+            SplitTable::ParquetRowGroups { file, .. } => read(file),
+        }
+    }
+
+    // Take global row `indices` and return the row count
+    pub async fn take(&self, indices: &[usize], columns: &[String]) -> Result<usize> {
+        let take = |path: &Path, indices: &[usize]| -> Result<usize> {
+            Ok(parquet::take_from_file(path, indices, columns)?.num_rows())
+        };
+        match self {
+            SplitTable::LanceFragments(dir) => {
+                let offsets: Vec<u64> = indices.iter().map(|&i| i as u64).collect();
+                Ok(dataset::take(dir.uri(), &offsets, columns)
+                    .await?
+                    .num_rows())
+            }
+            SplitTable::ParquetFiles { files, starts, .. } => {
+                // Route every index to the file holding it, as local indices
+                let mut rows = 0;
+                for (i, file) in files.iter().enumerate() {
+                    let start = starts[i];
+                    let end = starts.get(i + 1).copied().unwrap_or(usize::MAX);
+                    let local: Vec<usize> = indices
+                        .iter()
+                        .filter(|&&index| index >= start && index < end)
+                        .map(|&index| index - start)
+                        .collect();
+                    if !local.is_empty() {
+                        rows += take(file, &local)?;
+                    }
+                }
+                Ok(rows)
+            }
+            SplitTable::ParquetRowGroups { file, .. } => take(file, indices),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::error::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+// I/O issued by a single read, as seen by the format's reader.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadStats {
+    pub bytes_read: u64,
+    pub requests: u64,
+}
+
+// Resamples drawn for the bootstrap confidence interval
+const BOOTSTRAP_RESAMPLES: usize = 1000;
+// A timing is noisy when its stddev is above this fraction of the mean
+const NOISY_CV: f64 = 0.1;
+// Below this many samples the spread says little about run to run
+// variation, so there is no confidence interval or noise flag
+pub const MIN_SAMPLES: usize = 5;
+
+// Distribution of repeated timings of one operation, in milliseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimingStats {
+    pub samples: usize,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub mean_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub stddev_ms: f64,
+    // 95% bootstrap confidence interval of the median, unset below
+    // `MIN_SAMPLES` like `noisy`
+    pub ci_low_ms: Option<f64>,
+    pub ci_high_ms: Option<f64>,
+    pub noisy: Option<bool>,
+}
+
+impl TimingStats {
+    // `seed` drives the bootstrap resampling so reports are reproducible
+    pub fn from_samples(samples: &[Duration], seed: u64) -> TimingStats {
+        if samples.is_empty() {
+            return TimingStats::default();
+        }
+        let mut ms: Vec<f64> = samples.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        ms.sort_by(f64::total_cmp);
+        let at = |sorted: &[f64], p: f64| percentile(sorted, p).expect("samples are not empty");
+
+        let n = ms.len() as f64;
+        let mean_ms = ms.iter().sum::<f64>() / n;
+        let stddev_ms = if ms.len() > 1 {
+            (ms.iter().map(|x| (x - mean_ms).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+
+        let mut stats = TimingStats {
+            samples: ms.len(),
+            min_ms: ms[0],
+            median_ms: at(&ms, 50.0),
+            mean_ms,
+            p95_ms: at(&ms, 95.0),
+            p99_ms: at(&ms, 99.0),
+            stddev_ms,
+            ..TimingStats::default()
+        };
+        if ms.len() < MIN_SAMPLES {
+            return stats;
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut medians: Vec<f64> = (0..BOOTSTRAP_RESAMPLES)
+            .map(|_| {
+                let mut resample: Vec<f64> = (0..ms.len())
+                    .map(|_| ms[rng.gen_range(0..ms.len())])
+                    .collect();
+                resample.sort_by(f64::total_cmp);
+                at(&resample, 50.0)
+            })
+            .collect();
+        medians.sort_by(f64::total_cmp);
+        stats.ci_low_ms = Some(at(&medians, 2.5));
+        stats.ci_high_ms = Some(at(&medians, 97.5));
+        stats.noisy = Some(mean_ms > 0.0 && stddev_ms / mean_ms > NOISY_CV);
+        stats
+    }
+}
+
+// Linear interpolation between the closest ranks of an ascending slice,
+// `None` if it is empty
+pub fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64))
+}
+
+// Time `repetitions` runs of `op` after `warmup` unmeasured ones, returning
+// the distribution and the output of the last run
+pub fn measure<T>(
+    warmup: usize,
+    repetitions: usize,
+    seed: u64,
+    mut op: impl FnMut() -> Result<T>,
+) -> Result<(TimingStats, T)> {
+    for _ in 0..warmup {
+        op()?;
+    }
+    let mut samples = Vec::new();
+    let mut output = None;
+    for _ in 0..repetitions.max(1) {
+        let start = Instant::now();
+        let result = op()?;
+        samples.push(start.elapsed());
+        // Only now drops the previous output, so cleaning it up, e.g.
+        // deleting a written dataset, isn't timed
+        output = Some(result);
+    }
+    Ok((TimingStats::from_samples(&samples, seed), output.unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&ms| Duration::from_millis(ms)).collect()
+    }
+
+    #[test]
+    fn percentile_interpolates_between_ranks() {
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(percentile(&sorted, 0.0), Some(1.0));
+        assert_eq!(percentile(&sorted, 100.0), Some(4.0));
+        assert!(close(percentile(&sorted, 50.0).unwrap(), 2.5));
+        assert!(close(percentile(&sorted, 25.0).unwrap(), 1.75));
+    }
+
+    #[test]
+    fn percentile_of_one_or_no_values() {
+        assert_eq!(percentile(&[7.0], 0.0), Some(7.0));
+        assert_eq!(percentile(&[7.0], 99.0), Some(7.0));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn stats_of_known_samples() {
+        let stats = TimingStats::from_samples(&millis(&[5, 1, 4, 2, 3]), 1);
+        assert_eq!(stats.samples, 5);
+        assert!(close(stats.min_ms, 1.0));
+        assert!(close(stats.median_ms, 3.0));
+        assert!(close(stats.mean_ms, 3.0));
+        // Sample stddev of 1..=5 is sqrt(2.5)
+        assert!(close(stats.stddev_ms, 2.5_f64.sqrt()));
+        assert!(close(stats.p95_ms, 4.8));
+        assert!(close(stats.p99_ms, 4.96));
+        assert_eq!(stats.noisy, Some(true));
+    }
+
+    #[test]
+    fn bootstrap_interval_brackets_the_median() {
+        let samples = millis(&[10, 12, 11, 13, 30, 9, 10, 11, 12, 10]);
+        let stats = TimingStats::from_samples(&samples, 7);
+        let (low, high) = (stats.ci_low_ms.unwrap(), stats.ci_high_ms.unwrap());
+        assert!(low <= stats.median_ms);
+        assert!(stats.median_ms <= high);
+        assert!(low >= stats.min_ms);
+        // The same seed resamples the same way
+        assert_eq!(stats, TimingStats::from_samples(&samples, 7));
+    }
+
+    #[test]
+    fn identical_samples_have_no_spread() {
+        let stats = TimingStats::from_samples(&millis(&[2; MIN_SAMPLES]), 1);
+        assert!(close(stats.stddev_ms, 0.0));
+        assert_eq!(stats.ci_low_ms, Some(2.0));
+        assert_eq!(stats.ci_high_ms, Some(2.0));
+        assert_eq!(stats.noisy, Some(false));
+    }
+
+    #[test]
+    fn few_samples_give_no_interval() {
+        let stats = TimingStats::from_samples(&millis(&[1, 9]), 1);
+        assert!(close(stats.median_ms, 5.0));
+        assert_eq!(stats.ci_low_ms, None);
+        assert_eq!(stats.ci_high_ms, None);
+        assert_eq!(stats.noisy, None);
+    }
+
+    #[test]
+    fn no_samples_give_defaults() {
+        assert_eq!(TimingStats::from_samples(&[], 1), TimingStats::default());
+    }
+}
@@ -0,0 +1,595 @@
+use crate::data::{SchemaKind, ValueDistribution, DEFAULT_SEED};
+use crate::error::{Error, Result};
+use crate::format::{FormatConfig, LanceCompression};
+use crate::projection::ColumnSelection;
+use crate::provenance::Provenance;
+use crate::results::{append_records, read_records, Record, ResultFormat};
+use crate::size::{batch_size, BatchSize};
+use crate::stats::{measure, TimingStats};
+use arrow_array::RecordBatch;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::runtime::Runtime;
+
+// An experiment file. Every combination of the dimensions is one cell; a
+// dimension left out uses the default noted next to it.
+//
+//     results = "rle-pages.jsonl"    # defaults to the config path with .jsonl
+//     seed = 42
+//     repetitions = 10
+//     warmup = 1
+//
+//     [dimensions]
+//     format = ["lance", "parquet"]
+//     version = ["2.0", "2.1"]                  # lance only, default 2.1
+//     compression = ["bitpacking", "rle", "zstd"] # each format uses the ones it knows
+//     page_size = [65536, 1048576]              # bytes, default: writer's own
+//     rows = [10000, 100000]
+//     schema = ["nested", "flat"]               # default nested
+//     distribution = ["mixed", "runs"]          # default mixed
+//     access = ["scan", "point", "random-100"]  # default scan
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SweepConfig {
+    pub results: Option<PathBuf>,
+    #[serde(default = "default_seed")]
+    pub seed: u64,
+    #[serde(default = "default_repetitions")]
+    pub repetitions: usize,
+    #[serde(default = "default_warmup")]
+    pub warmup: usize,
+    pub dimensions: Dimensions,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dimensions {
+    pub format: Vec<String>,
+    #[serde(default)]
+    pub version: Vec<String>,
+    #[serde(default)]
+    pub compression: Vec<String>,
+    #[serde(default)]
+    pub page_size: Vec<u64>,
+    pub rows: Vec<usize>,
+    #[serde(default)]
+    pub schema: Vec<String>,
+    #[serde(default)]
+    pub distribution: Vec<String>,
+    #[serde(default)]
+    pub access: Vec<String>,
+}
+
+fn default_seed() -> u64 {
+    DEFAULT_SEED
+}
+
+fn default_repetitions() -> usize {
+    10
+}
+
+fn default_warmup() -> usize {
+    1
+}
+
+// How a cell reads the file back after writing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessPattern {
+    // Full scan of every column
+    Scan,
+    // Take of the middle row
+    Point,
+    // Take of K distinct rows drawn with the sweep seed
+    Random(usize),
+    // Take of K consecutive rows around the middle
+    Range(usize),
+}
+
+impl AccessPattern {
+    fn indices(&self, num_rows: usize, seed: u64) -> Vec<usize> {
+        match *self {
+            AccessPattern::Scan => Vec::new(),
+            AccessPattern::Point => vec![num_rows / 2],
+            AccessPattern::Random(k) => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut indices =
+                    rand::seq::index::sample(&mut rng, num_rows, k.min(num_rows)).into_vec();
+                indices.sort_unstable();
+                indices
+            }
+            AccessPattern::Range(k) => {
+                let k = k.min(num_rows);
+                let start = (num_rows / 2).saturating_sub(k / 2).min(num_rows - k);
+                (start..start + k).collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for AccessPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessPattern::Scan => write!(f, "scan"),
+            AccessPattern::Point => write!(f, "point"),
+            AccessPattern::Random(k) => write!(f, "random-{}", k),
+            AccessPattern::Range(k) => write!(f, "range-{}", k),
+        }
+    }
+}
+
+impl FromStr for AccessPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let count = |k: &str| {
+            k.parse::<usize>()
+                .map_err(|_| format!("invalid row count in access pattern `{}`", s))
+        };
+        match s {
+            "scan" => Ok(AccessPattern::Scan),
+            "point" => Ok(AccessPattern::Point),
+            _ => match s.split_once('-') {
+                Some(("random", k)) => Ok(AccessPattern::Random(count(k)?)),
+                Some(("range", k)) => Ok(AccessPattern::Range(count(k)?)),
+                _ => Err(format!(
+                    "unknown access pattern `{}`, expected scan, point, random-K or range-K",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+// One point of the Cartesian product
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub config: FormatConfig,
+    pub page_size: Option<u64>,
+    pub rows: usize,
+    pub schema: SchemaKind,
+    pub distribution: ValueDistribution,
+    pub access: AccessPattern,
+}
+
+impl Cell {
+    // Stable name used to recognise completed cells when resuming
+    pub fn id(&self) -> String {
+        format!(
+            "{}/page-{}/{}/{}/{}/{}",
+            self.config.name(),
+            self.page_size
+                .map_or("default".to_string(), |size| size.to_string()),
+            self.rows,
+            self.schema.name(),
+            self.distribution.name(),
+            self.access
+        )
+    }
+}
+
+impl SweepConfig {
+    pub fn load(path: &Path) -> Result<SweepConfig> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn results_path(&self, config_path: &Path) -> PathBuf {
+        self.results
+            .clone()
+            .unwrap_or_else(|| config_path.with_extension("jsonl"))
+    }
+
+    // Expand the dimensions into cells, data dimensions outermost so
+    // consecutive cells can share a generated batch. Combinations a format
+    // can't express, like a Parquet version, collapse into one cell.
+    pub fn cells(&self) -> Result<Vec<Cell>> {
+        let dims = &self.dimensions;
+        let invalid = Error::InvalidInput;
+        let schemas: Vec<SchemaKind> =
+            parse_all(&dims.schema, SchemaKind::Nested).map_err(invalid)?;
+        let distributions: Vec<ValueDistribution> =
+            parse_all(&dims.distribution, ValueDistribution::Mixed).map_err(invalid)?;
+        let accesses: Vec<AccessPattern> =
+            parse_all(&dims.access, AccessPattern::Scan).map_err(invalid)?;
+        let page_sizes: Vec<Option<u64>> = if dims.page_size.is_empty() {
+            vec![None]
+        } else {
+            dims.page_size.iter().copied().map(Some).collect()
+        };
+
+        // A value no format accepts is a typo, not something to skip
+        for compression in &dims.compression {
+            let lance = compression.parse::<LanceCompression>().is_ok();
+            let parquet = format!("parquet-{}", compression)
+                .parse::<FormatConfig>()
+                .is_ok();
+            if !lance && !parquet {
+                return Err(Error::InvalidInput(format!(
+                    "unknown compression `{}` in sweep",
+                    compression
+                )));
+            }
+        }
+        for version in &dims.version {
+            format!("lance-v{}", version.trim_start_matches('v'))
+                .parse::<FormatConfig>()
+                .map_err(|_| {
+                    Error::InvalidInput(format!("unknown lance version `{}` in sweep", version))
+                })?;
+        }
+
+        let mut configs = Vec::new();
+        for format in &dims.format {
+            for config in format_configs(format, &dims.version, &dims.compression)? {
+                if !configs.contains(&config) {
+                    configs.push(config);
+                }
+            }
+        }
+
+        let mut cells = Vec::new();
+        for &schema in &schemas {
+            for &distribution in &distributions {
+                for &rows in &dims.rows {
+                    for &config in &configs {
+                        for &page_size in &page_sizes {
+                            for &access in &accesses {
+                                cells.push(Cell {
+                                    config,
+                                    page_size,
+                                    rows,
+                                    schema,
+                                    distribution,
+                                    access,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(cells)
+    }
+}
+
+fn parse_all<T: FromStr<Err = String>>(values: &[String], default: T) -> Result<Vec<T>, String> {
+    if values.is_empty() {
+        return Ok(vec![default]);
+    }
+    values.iter().map(|value| value.parse()).collect()
+}
+
+// Every config of `format` the version and compression dimensions describe.
+// Values that don't apply to the format are ignored, and a format with no
+// applicable values falls back to its default.
+fn format_configs(
+    format: &str,
+    versions: &[String],
+    compressions: &[String],
+) -> Result<Vec<FormatConfig>> {
+    let applicable = |names: Vec<String>| -> Vec<FormatConfig> {
+        names.iter().filter_map(|name| name.parse().ok()).collect()
+    };
+
+    let configs = match format {
+        "lance" => {
+            let versions: Vec<String> = if versions.is_empty() {
+                vec![String::new()]
+            } else {
+                versions
+                    .iter()
+                    .map(|v| format!("-v{}", v.trim_start_matches('v')))
+                    .collect()
+            };
+            let mut names = Vec::new();
+            for version in &versions {
+                for compression in compressions {
+                    names.push(format!("lance{}-{}", version, compression));
+                }
+            }
+            let configs = applicable(names);
+            if configs.is_empty() {
+                versions
+                    .iter()
+                    .map(|version| format!("lance{}", version).parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(Error::InvalidInput)?
+            } else {
+                configs
+            }
+        }
+        "parquet" => {
+            let names = compressions
+                .iter()
+                .map(|compression| format!("parquet-{}", compression))
+                .collect();
+            let configs = applicable(names);
+            if configs.is_empty() {
+                vec![FormatConfig::PARQUET]
+            } else {
+                configs
+            }
+        }
+        _ => {
+            return Err(Error::InvalidInput(format!(
+                "unknown format `{}` in sweep, expected lance or parquet",
+                format
+            )))
+        }
+    };
+    Ok(configs)
+}
+
+// Runs the cells of a `SweepConfig`, appending each cell's records to the
+// results file as soon as it finishes
+pub struct Sweep<W: Write> {
+    config: SweepConfig,
+    results: PathBuf,
+    provenance: Provenance,
+    out: W,
+    rt: Runtime,
+}
+
+impl<W: Write> Sweep<W> {
+    pub fn new(
+        config: SweepConfig,
+        results: PathBuf,
+        provenance: Provenance,
+        out: W,
+    ) -> Result<Self> {
+        Ok(Sweep {
+            config,
+            results,
+            provenance,
+            out,
+            rt: Runtime::new()?,
+        })
+    }
+
+    // Cells whose latest attempt worked. Each attempt appends all of its
+    // records at once and a failed one ends in an error record, so the last
+    // record of a cell decides. A crash can leave a partial last line behind,
+    // which is dropped so the file stays readable.
+    fn completed(&self) -> Result<HashSet<String>> {
+        let Ok(contents) = fs::read_to_string(&self.results) else {
+            return Ok(HashSet::new());
+        };
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            let end = contents.rfind('\n').map_or(0, |i| i + 1);
+            fs::write(&self.results, &contents[..end])?;
+        }
+
+        let mut latest: HashMap<String, bool> = HashMap::new();
+        for record in read_records(&self.results, ResultFormat::Json)? {
+            if let Some(cell) = record.cell {
+                latest.insert(cell, record.error.is_none());
+            }
+        }
+        Ok(latest
+            .into_iter()
+            .filter(|(_, worked)| *worked)
+            .map(|(cell, _)| cell)
+            .collect())
+    }
+
+    // Run every cell not already completed and return how many failed
+    pub fn run(&mut self) -> Result<usize> {
+        let cells = self.config.cells()?;
+        let completed = self.completed()?;
+        let pending: Vec<&Cell> = cells
+            .iter()
+            .filter(|cell| !completed.contains(&cell.id()))
+            .collect();
+
+        self.provenance.write_markdown(&mut self.out)?;
+        self.provenance.append_sidecar(&self.results)?;
+
+        writeln!(self.out, "\n=== Sweep ===")?;
+        writeln!(
+            self.out,
+            "\n{} cell(s), {} already completed, results in {}",
+            cells.len(),
+            cells.len() - pending.len(),
+            self.results.display()
+        )?;
+        writeln!(
+            self.out,
+            "\n| Cell | Size | Ratio | Write ms | Access ms | Bytes read |"
+        )?;
+        writeln!(
+            self.out,
+            "|------|------|-------|----------|-----------|------------|"
+        )?;
+
+        let mut failed = 0;
+        let mut batch: Option<(
+            (SchemaKind, ValueDistribution, usize),
+            RecordBatch,
+            BatchSize,
+        )> = None;
+        for cell in pending {
+            let key = (cell.schema, cell.distribution, cell.rows);
+            if batch.as_ref().map(|(k, _, _)| *k) != Some(key) {
+                let generated =
+                    cell.schema
+                        .generate_with(cell.rows, self.config.seed, cell.distribution);
+                let memory = batch_size(&generated)?;
+                batch = Some((key, generated, memory));
+            }
+            let (_, data, memory) = batch.as_ref().unwrap();
+
+            let records = match self.run_cell(cell, data, *memory) {
+                Ok(records) => {
+                    let (write, access) = (&records[0], &records[1]);
+                    writeln!(
+                        self.out,
+                        "| {} | {} | {:.2}x | {:.2} | {:.2} | {} |",
+                        cell.id(),
+                        write.file_bytes.unwrap_or_default(),
+                        write.ratio.unwrap_or_default(),
+                        write.latency_ms.unwrap_or_default(),
+                        access.latency_ms.unwrap_or_default(),
+                        access.bytes_read.unwrap_or_default()
+                    )?;
+                    records
+                }
+                Err(error) => {
+                    failed += 1;
+                    writeln!(self.out, "| {} | failed: {} | | | | |", cell.id(), error)?;
+                    vec![Record {
+                        step: "sweep".to_string(),
+                        error: Some(error.to_string()),
+                        ..cell_record(cell)
+                    }]
+                }
+            };
+            append_records(&self.provenance.stamp(&records), &self.results)?;
+            self.out.flush()?;
+        }
+        Ok(failed)
+    }
+
+    // Write and read back one cell, returning its write and access records
+    fn run_cell(&self, cell: &Cell, batch: &RecordBatch, memory: BatchSize) -> Result<Vec<Record>> {
+        let (warmup, repetitions, seed) = (
+            self.config.warmup,
+            self.config.repetitions,
+            self.config.seed,
+        );
+        let throughput =
+            |timing: &TimingStats| memory.logical as f64 / 1e6 / (timing.median_ms / 1000.0);
+
+        let (write, bytes) = measure(warmup, repetitions, seed, || {
+            self.rt
+                .block_on(cell.config.write_with(batch.clone(), cell.page_size))
+        })?;
+
+        let columns = ColumnSelection::All.column_paths(&batch.schema());
+        let indices = cell.access.indices(cell.rows, seed);
+        let (access, stats) = measure(warmup, repetitions, seed, || match cell.access {
+            AccessPattern::Scan => Ok(self
+                .rt
+                .block_on(cell.config.read_projected(&bytes, &columns))?
+                .1),
+            _ => Ok(self
+                .rt
+                .block_on(cell.config.take_projected(&bytes, &indices, &columns))?
+                .1),
+        })?;
+
+        let base = Record {
+            file_bytes: Some(bytes.len() as u64),
+            logical_bytes: Some(memory.logical),
+            physical_bytes: Some(memory.physical),
+            ..cell_record(cell)
+        };
+        Ok(vec![
+            Record {
+                step: "write".to_string(),
+                ratio: Some(memory.logical as f64 / bytes.len() as f64),
+                latency_ms: Some(write.median_ms),
+                throughput_mbps: Some(throughput(&write)),
+                timing: Some(write),
+                ..base.clone()
+            },
+            Record {
+                step: cell.access.to_string(),
+                latency_ms: Some(access.median_ms),
+                throughput_mbps: (cell.access == AccessPattern::Scan).then(|| throughput(&access)),
+                bytes_read: Some(stats.bytes_read),
+                requests: Some(stats.requests),
+                timing: Some(access),
+                ..base
+            },
+        ])
+    }
+}
+
+fn cell_record(cell: &Cell) -> Record {
+    Record {
+        format: cell.config.format().to_string(),
+        config: cell.config.name(),
+        schema: cell.schema.name().to_string(),
+        rows: cell.rows as u64,
+        cell: Some(cell.id()),
+        page_size: cell.page_size,
+        distribution: Some(cell.distribution.name().to_string()),
+        ..Record::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::DatasetDir;
+
+    fn sweep(dir: &DatasetDir) -> Sweep<Vec<u8>> {
+        let config: SweepConfig = toml::from_str(
+            "repetitions = 1\nwarmup = 0\n[dimensions]\nformat = [\"parquet\"]\nrows = [100]",
+        )
+        .unwrap();
+        let results = dir.path().join("results.jsonl");
+        Sweep::new(
+            config,
+            results,
+            Provenance::capture(7, String::new()),
+            Vec::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let config: SweepConfig = toml::from_str(
+            "[dimensions]\nformat = [\"lance\"]\nversion = [\"2.1\", \"9.9\"]\nrows = [100]",
+        )
+        .unwrap();
+        assert!(config.cells().is_err());
+    }
+
+    #[test]
+    fn resumes_cells_that_failed_before() {
+        let dir = DatasetDir::new().unwrap();
+        fs::create_dir_all(dir.path()).unwrap();
+        let mut first = sweep(&dir);
+        let cell = first.config.cells().unwrap().remove(0);
+
+        // An earlier session failed the only cell
+        let failed = Record {
+            step: "sweep".to_string(),
+            error: Some("interrupted".to_string()),
+            ..cell_record(&cell)
+        };
+        append_records(&[failed.clone()], &first.results).unwrap();
+        assert!(first.completed().unwrap().is_empty());
+
+        // The first resume retries it and it now works
+        assert_eq!(first.run().unwrap(), 0);
+        assert!(first.completed().unwrap().contains(&cell.id()));
+        let records = read_records(&first.results, ResultFormat::Json).unwrap();
+        assert_eq!(records.len(), 3);
+
+        // The second finds nothing left to do
+        let mut second = sweep(&dir);
+        assert_eq!(second.run().unwrap(), 0);
+        let output = std::str::from_utf8(&second.out).unwrap();
+        assert!(output.contains("1 cell(s), 1 already completed"));
+        let records = read_records(&second.results, ResultFormat::Json).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].session, Some(first.provenance.session.clone()));
+
+        // Each session is kept in the sidecar
+        let sidecar = fs::read_to_string(Provenance::sidecar_path(&second.results)).unwrap();
+        assert_eq!(sidecar.lines().count(), 2);
+
+        // A later failure makes the cell pending again
+        append_records(&[failed], &second.results).unwrap();
+        assert!(second.completed().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+use crate::error::{Error, Result};
+use arrow_array::{RecordBatch, UInt32Array};
+use arrow_select::take::take_record_batch;
+
+// A take at arbitrary indices. Both readers need distinct rows in file
+// order, so those are read and then put back in the order asked for,
+// duplicates included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TakePlan {
+    // Distinct indices, ascending
+    pub rows: Vec<usize>,
+    // `positions[i]` is where requested index `i` sits in `rows`
+    positions: Vec<u32>,
+}
+
+impl TakePlan {
+    pub fn new(indices: &[usize], num_rows: usize) -> Result<TakePlan> {
+        if let Some(&index) = indices.iter().find(|&&i| i >= num_rows) {
+            return Err(Error::InvalidInput(format!(
+                "take index {} out of range for {} rows",
+                index, num_rows
+            )));
+        }
+        let mut rows = indices.to_vec();
+        rows.sort_unstable();
+        rows.dedup();
+        let positions = indices
+            .iter()
+            .map(|index| rows.binary_search(index).unwrap() as u32)
+            .collect();
+        Ok(TakePlan { rows, positions })
+    }
+
+    // `batch` holds `rows` in order; return them in the requested order
+    pub fn restore(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        let in_order = self.positions.len() == self.rows.len()
+            && self
+                .positions
+                .iter()
+                .enumerate()
+                .all(|(i, &p)| p as usize == i);
+        if in_order {
+            return Ok(batch);
+        }
+        let positions = UInt32Array::from(self.positions.clone());
+        Ok(take_record_batch(&batch, &positions)?)
+    }
+}
@@ -0,0 +1,184 @@
+use crate::error::Result;
+use crate::format::FormatConfig;
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, RecordBatch, UInt64Array};
+use arrow_select::take::take_record_batch;
+use std::fmt;
+
+// First difference found between a source batch and what a format decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub column: String,
+    pub row: Option<usize>,
+    pub reason: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.row {
+            Some(row) => write!(f, "column `{}` row {}: {}", self.column, row, self.reason),
+            None => write!(f, "column `{}`: {}", self.column, self.reason),
+        }
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+fn mismatch(column: &str, row: Option<usize>, reason: impl Into<String>) -> Mismatch {
+    Mismatch {
+        column: column.to_string(),
+        row,
+        reason: reason.into(),
+    }
+}
+
+// Check that `actual`, as returned by a reader, holds exactly the rows of
+// `expected`. Columns are matched by name and compared value by value, so
+// field metadata added by a writer does not count as a difference, while
+// null positions and float bit patterns (NaN, -0.0) do.
+pub fn verify_batches(expected: &RecordBatch, actual: &[RecordBatch]) -> Result<(), Mismatch> {
+    let actual = match actual.first() {
+        Some(first) => arrow_select::concat::concat_batches(&first.schema(), actual)
+            .map_err(|e| mismatch("<batch>", None, e.to_string()))?,
+        None => RecordBatch::new_empty(expected.schema()),
+    };
+
+    if expected.num_rows() != actual.num_rows() {
+        return Err(mismatch(
+            "<batch>",
+            None,
+            format!(
+                "expected {} rows, got {}",
+                expected.num_rows(),
+                actual.num_rows()
+            ),
+        ));
+    }
+    if expected.num_columns() != actual.num_columns() {
+        return Err(mismatch(
+            "<batch>",
+            None,
+            format!(
+                "expected {} columns, got {}",
+                expected.num_columns(),
+                actual.num_columns()
+            ),
+        ));
+    }
+
+    for (field, column) in expected.schema().fields().iter().zip(expected.columns()) {
+        let other = actual
+            .column_by_name(field.name())
+            .ok_or_else(|| mismatch(field.name(), None, "missing from decoded batch"))?;
+        compare_arrays(field.name(), column.as_ref(), other.as_ref())?;
+    }
+    Ok(())
+}
+
+// Write `batch` with `config` and check the decoded result against it
+pub async fn verify_roundtrip(config: FormatConfig, batch: &RecordBatch) -> Result<()> {
+    let bytes = config.write(batch.clone()).await?;
+    verify_bytes(config, batch, &bytes).await
+}
+
+// Check a full scan of `bytes`, and takes of the first, middle and last rows
+// on their own and together, against the `batch` they were written from.
+pub async fn verify_bytes(config: FormatConfig, batch: &RecordBatch, bytes: &[u8]) -> Result<()> {
+    let scanned = config.read(bytes).await?;
+    verify_batches(batch, &scanned)?;
+
+    if batch.num_rows() > 0 {
+        let (middle, last) = (batch.num_rows() / 2, batch.num_rows() - 1);
+        let mut together = vec![0, middle, last];
+        together.dedup();
+        for indices in [vec![0], vec![middle], vec![last], together] {
+            let taken = config.take(bytes, &indices).await?;
+            let rows = UInt64Array::from_iter_values(indices.iter().map(|&i| i as u64));
+            let expected = take_record_batch(batch, &rows)?;
+            verify_batches(&expected, &[taken]).map_err(|mut e| {
+                e.reason = format!("take of rows {:?}: {}", indices, e.reason);
+                e
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn compare_arrays(path: &str, expected: &dyn Array, actual: &dyn Array) -> Result<(), Mismatch> {
+    if expected.len() != actual.len() {
+        return Err(mismatch(
+            path,
+            None,
+            format!("expected length {}, got {}", expected.len(), actual.len()),
+        ));
+    }
+
+    // Null positions are checked row by row because writers are free to
+    // materialize an all-valid null buffer or drop it entirely
+    for row in 0..expected.len() {
+        if expected.is_null(row) != actual.is_null(row) {
+            return Err(mismatch(
+                path,
+                Some(row),
+                format!(
+                    "expected null={}, got null={}",
+                    expected.is_null(row),
+                    actual.is_null(row)
+                ),
+            ));
+        }
+    }
+
+    match (expected.as_struct_opt(), actual.as_struct_opt()) {
+        (Some(expected), Some(actual)) => {
+            if expected.num_columns() != actual.num_columns() {
+                return Err(mismatch(
+                    path,
+                    None,
+                    format!(
+                        "expected {} children, got {}",
+                        expected.num_columns(),
+                        actual.num_columns()
+                    ),
+                ));
+            }
+            for (field, child) in expected.fields().iter().zip(expected.columns()) {
+                let child_path = format!("{}.{}", path, field.name());
+                let other = actual
+                    .column_by_name(field.name())
+                    .ok_or_else(|| mismatch(&child_path, None, "missing from decoded struct"))?;
+                compare_arrays(&child_path, child.as_ref(), other.as_ref())?;
+            }
+            Ok(())
+        }
+        (Some(_), None) | (None, Some(_)) => Err(mismatch(
+            path,
+            None,
+            format!(
+                "expected type {}, got {}",
+                expected.data_type(),
+                actual.data_type()
+            ),
+        )),
+        (None, None) => {
+            if expected.data_type() != actual.data_type() {
+                return Err(mismatch(
+                    path,
+                    None,
+                    format!(
+                        "expected type {}, got {}",
+                        expected.data_type(),
+                        actual.data_type()
+                    ),
+                ));
+            }
+            if expected.to_data() == actual.to_data() {
+                return Ok(());
+            }
+            // Narrow the failure down to the first differing row
+            let row = (0..expected.len())
+                .find(|&row| expected.slice(row, 1).to_data() != actual.slice(row, 1).to_data());
+            Err(mismatch(path, row, "decoded value differs from source"))
+        }
+    }
+}
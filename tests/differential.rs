@@ -0,0 +1,194 @@
+use arrow_array::{RecordBatch, UInt32Array};
+use lance_rle_benchmark::data::{
+    generate_flat_record_batch, generate_nested_record_batch, generate_nullable_record_batch,
+};
+use lance_rle_benchmark::projection::ColumnSelection;
+use lance_rle_benchmark::verify::verify_batches;
+use lance_rle_benchmark::{lance, parquet};
+use proptest::prelude::*;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+const NUM_ROWS: usize = 2_000;
+
+// One batch written by both adapters
+struct Fixture {
+    batch: RecordBatch,
+    lance: Vec<u8>,
+    parquet: Vec<u8>,
+}
+
+impl Fixture {
+    fn new(generate_fn: fn(usize) -> RecordBatch) -> Self {
+        let rt = Runtime::new().unwrap();
+        let batch = generate_fn(NUM_ROWS);
+        let lance = rt
+            .block_on(lance::write_bytes(batch.clone(), false, true))
+            .unwrap();
+        let parquet = parquet::write_bytes(batch.clone()).unwrap();
+        Self {
+            batch,
+            lance,
+            parquet,
+        }
+    }
+}
+
+fn nested() -> &'static Fixture {
+    static FIXTURE: OnceLock<Fixture> = OnceLock::new();
+    FIXTURE.get_or_init(|| Fixture::new(generate_nested_record_batch))
+}
+
+fn flat() -> &'static Fixture {
+    static FIXTURE: OnceLock<Fixture> = OnceLock::new();
+    FIXTURE.get_or_init(|| Fixture::new(generate_flat_record_batch))
+}
+
+fn nullable() -> &'static Fixture {
+    static FIXTURE: OnceLock<Fixture> = OnceLock::new();
+    FIXTURE.get_or_init(|| Fixture::new(generate_nullable_record_batch))
+}
+
+fn fixtures() -> [(&'static str, &'static Fixture); 3] {
+    [
+        ("nested", nested()),
+        ("flat", flat()),
+        ("nullable", nullable()),
+    ]
+}
+
+// Rows of the source batch a take of `indices` should return
+fn expected_take(batch: &RecordBatch, indices: &[usize]) -> RecordBatch {
+    let indices = UInt32Array::from_iter_values(indices.iter().map(|&i| i as u32));
+    arrow_select::take::take_record_batch(batch, &indices).unwrap()
+}
+
+fn check_take(name: &str, fixture: &Fixture, indices: &[usize]) -> Result<(), String> {
+    let rt = Runtime::new().unwrap();
+    let from_lance = rt
+        .block_on(lance::take_rows_from_bytes(&fixture.lance, indices))
+        .map_err(|e| format!("{}: lance take of {:?} failed: {}", name, indices, e))?;
+    let from_parquet = parquet::take_rows_from_bytes(&fixture.parquet, indices)
+        .map_err(|e| format!("{}: parquet take of {:?} failed: {}", name, indices, e))?;
+
+    verify_batches(&from_parquet, &[from_lance]).map_err(|e| {
+        format!(
+            "{}: lance and parquet disagree on {:?}: {}",
+            name, indices, e
+        )
+    })?;
+    verify_batches(&expected_take(&fixture.batch, indices), &[from_parquet])
+        .map_err(|e| format!("{}: take of {:?} differs from source: {}", name, indices, e))
+}
+
+#[test]
+fn full_scans_match() {
+    let rt = Runtime::new().unwrap();
+    for (name, fixture) in fixtures() {
+        let from_lance = rt.block_on(lance::read_bytes(&fixture.lance)).unwrap();
+        let from_parquet = parquet::read_bytes(&fixture.parquet).unwrap();
+
+        if let Err(e) = verify_batches(&fixture.batch, &from_lance) {
+            panic!("{}: lance scan differs from source: {}", name, e);
+        }
+        if let Err(e) = verify_batches(&fixture.batch, &from_parquet) {
+            panic!("{}: parquet scan differs from source: {}", name, e);
+        }
+    }
+}
+
+#[test]
+fn projected_scans_match() {
+    let rt = Runtime::new().unwrap();
+    let selections = [
+        ColumnSelection::Single(0),
+        ColumnSelection::Single(3826),
+        ColumnSelection::FirstK(50),
+        ColumnSelection::RandomK { k: 50, seed: 7 },
+    ];
+
+    for (name, fixture) in fixtures() {
+        for selection in &selections {
+            let columns = selection.column_paths(&fixture.batch.schema());
+            let (from_lance, _) = rt
+                .block_on(lance::read_bytes_projected(&fixture.lance, &columns))
+                .unwrap();
+            let (from_parquet, _) =
+                parquet::read_bytes_projected(&fixture.parquet, &columns).unwrap();
+
+            let from_parquet =
+                arrow_select::concat::concat_batches(&from_parquet[0].schema(), &from_parquet)
+                    .unwrap();
+            if let Err(e) = verify_batches(&from_parquet, &from_lance) {
+                panic!(
+                    "{} {}: projections disagree: {}",
+                    name,
+                    selection.label(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn out_of_range_take_fails() {
+    let rt = Runtime::new().unwrap();
+    for (name, fixture) in fixtures() {
+        for indices in [
+            vec![NUM_ROWS],
+            vec![NUM_ROWS + 1_000],
+            vec![NUM_ROWS - 1, NUM_ROWS],
+        ] {
+            let from_lance = rt.block_on(lance::take_rows_from_bytes(&fixture.lance, &indices));
+            assert!(from_lance.is_err(), "{}: lance took {:?}", name, indices);
+            let from_parquet = parquet::take_rows_from_bytes(&fixture.parquet, &indices);
+            assert!(
+                from_parquet.is_err(),
+                "{}: parquet took {:?}",
+                name,
+                indices
+            );
+        }
+    }
+}
+
+#[test]
+fn boundary_takes_match() {
+    for (name, fixture) in fixtures() {
+        check_take(name, fixture, &[0]).unwrap();
+        check_take(name, fixture, &[NUM_ROWS - 1]).unwrap();
+        check_take(name, fixture, &[0, NUM_ROWS - 1]).unwrap();
+        check_take(name, fixture, &[1023, 1024, 1025]).unwrap();
+    }
+}
+
+#[test]
+fn unordered_and_repeated_takes_match() {
+    for (name, fixture) in fixtures() {
+        check_take(name, fixture, &[NUM_ROWS - 1, 0]).unwrap();
+        check_take(name, fixture, &[5, 5, 5]).unwrap();
+        check_take(name, fixture, &[1025, 3, 1025, 0, 3]).unwrap();
+    }
+}
+
+// Indices in any order, repeats likely, drawn from a narrow range half the
+// time so runs of neighbouring rows come up too
+fn indices() -> impl Strategy<Value = Vec<usize>> {
+    prop_oneof![
+        prop::collection::vec(0..NUM_ROWS, 1..32),
+        (0..NUM_ROWS - 16).prop_flat_map(|start| prop::collection::vec(start..start + 16, 1..32)),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn arbitrary_takes_match(indices in indices()) {
+        for (name, fixture) in fixtures() {
+            let result = check_take(name, fixture, &indices);
+            prop_assert!(result.is_ok(), "{}", result.unwrap_err());
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch};
+use arrow_schema::{Field, Schema};
+use lance_rle_benchmark::lance;
+use lance_rle_benchmark::verify::verify_batches;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+// Lengths just around the sizes where Lance splits mini-blocks and pages,
+// mixed with arbitrary ones
+fn num_rows() -> impl Strategy<Value = usize> {
+    prop_oneof![
+        prop::sample::select(vec![
+            1, 2, 3, 1023, 1024, 1025, 4095, 4096, 4097, 8191, 8192, 8193, 65535, 65536, 65537,
+        ]),
+        1usize..10_000,
+    ]
+}
+
+// Keep only the low `width` bits so values fit the requested bit width
+fn mask(value: i64, width: u32) -> i64 {
+    if width >= 64 {
+        value
+    } else {
+        (value as u64 & ((1u64 << width) - 1)) as i64
+    }
+}
+
+// Repeat `runs` cyclically until `len` values have been produced
+fn expand_runs<T: Copy>(runs: &[(T, usize)], len: usize) -> Vec<T> {
+    let mut values = Vec::with_capacity(len);
+    while values.len() < len {
+        for &(value, run) in runs {
+            let run = run.min(len - values.len());
+            values.extend(std::iter::repeat(value).take(run));
+        }
+    }
+    values
+}
+
+fn int_values(len: usize) -> impl Strategy<Value = Vec<i64>> {
+    let runs = (
+        0u32..=64,
+        prop::collection::vec((any::<i64>(), prop_oneof![1usize..8, 8usize..2048]), 1..32),
+    )
+        .prop_map(move |(width, runs)| {
+            let runs: Vec<(i64, usize)> = runs
+                .into_iter()
+                .map(|(value, run)| (mask(value, width), run))
+                .collect();
+            expand_runs(&runs, len)
+        });
+    let same = any::<i64>().prop_map(move |value| vec![value; len]);
+    let alternating = (any::<i64>(), any::<i64>()).prop_map(move |(a, b)| {
+        (0..len)
+            .map(|i| if i % 2 == 0 { a } else { b })
+            .collect::<Vec<_>>()
+    });
+    let random =
+        (0u32..=64, prop::collection::vec(any::<i64>(), len)).prop_map(|(width, values)| {
+            values
+                .into_iter()
+                .map(|v| mask(v, width))
+                .collect::<Vec<_>>()
+        });
+
+    prop_oneof![runs, same, alternating, random]
+}
+
+fn float_value() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        Just(f64::NAN),
+        Just(f64::INFINITY),
+        Just(f64::NEG_INFINITY),
+        Just(-0.0),
+        Just(0.0),
+        any::<f64>(),
+    ]
+}
+
+fn float_values(len: usize) -> impl Strategy<Value = Vec<f64>> {
+    let runs = prop::collection::vec((float_value(), prop_oneof![1usize..8, 8usize..2048]), 1..32)
+        .prop_map(move |runs| expand_runs(&runs, len));
+    let same = float_value().prop_map(move |value| vec![value; len]);
+    let alternating = (float_value(), float_value()).prop_map(move |(a, b)| {
+        (0..len)
+            .map(|i| if i % 2 == 0 { a } else { b })
+            .collect::<Vec<_>>()
+    });
+    let random = prop::collection::vec(float_value(), len);
+
+    prop_oneof![runs, same, alternating, random]
+}
+
+// Validity for `len` rows: none, sparse, dense or entirely null
+fn validity(len: usize) -> impl Strategy<Value = Option<Vec<bool>>> {
+    prop_oneof![
+        Just(None),
+        prop::collection::vec(prop::bool::weighted(0.9), len).prop_map(Some),
+        prop::collection::vec(prop::bool::weighted(0.5), len).prop_map(Some),
+        Just(Some(vec![false; len])),
+    ]
+}
+
+fn apply_validity<T>(values: Vec<T>, validity: Option<Vec<bool>>) -> Vec<Option<T>> {
+    match validity {
+        Some(valid) => values
+            .into_iter()
+            .zip(valid)
+            .map(|(value, valid)| valid.then_some(value))
+            .collect(),
+        None => values.into_iter().map(Some).collect(),
+    }
+}
+
+fn column(len: usize) -> impl Strategy<Value = (ArrayRef, bool)> {
+    let ints = (int_values(len), validity(len)).prop_map(|(values, validity)| {
+        let nullable = validity.is_some();
+        let array = Int64Array::from(apply_validity(values, validity));
+        (Arc::new(array) as ArrayRef, nullable)
+    });
+    let floats = (float_values(len), validity(len)).prop_map(|(values, validity)| {
+        let nullable = validity.is_some();
+        let array = Float64Array::from(apply_validity(values, validity));
+        (Arc::new(array) as ArrayRef, nullable)
+    });
+    prop_oneof![ints, floats]
+}
+
+fn record_batch() -> impl Strategy<Value = RecordBatch> {
+    num_rows()
+        .prop_flat_map(|len| prop::collection::vec(column(len), 1..=4))
+        .prop_map(|columns| {
+            let fields: Vec<Field> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, (array, nullable))| {
+                    Field::new(format!("c{}", i), array.data_type().clone(), *nullable)
+                })
+                .collect();
+            let arrays = columns.into_iter().map(|(array, _)| array).collect();
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).unwrap()
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn rle_scan_roundtrip(batch in record_batch()) {
+        let rt = Runtime::new().unwrap();
+        let bytes = rt.block_on(lance::write_bytes(batch.clone(), false, true)).unwrap();
+        let decoded = rt.block_on(lance::read_bytes(&bytes)).unwrap();
+
+        let result = verify_batches(&batch, &decoded);
+        prop_assert!(result.is_ok(), "{}", result.unwrap_err());
+    }
+
+    #[test]
+    fn rle_take_roundtrip(batch in record_batch(), index in any::<Index>()) {
+        let rt = Runtime::new().unwrap();
+        let bytes = rt.block_on(lance::write_bytes(batch.clone(), false, true)).unwrap();
+        let row = index.index(batch.num_rows());
+        let taken = rt.block_on(lance::take_rows_from_bytes(&bytes, &[row])).unwrap();
+
+        let result = verify_batches(&batch.slice(row, 1), &[taken]);
+        prop_assert!(result.is_ok(), "row {}: {}", row, result.unwrap_err());
+    }
+}
@@ -0,0 +1,90 @@
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, RecordBatch};
+use lance_rle_benchmark::data::{
+    generate_flat_record_batch, generate_nested_record_batch, generate_nullable_record_batch,
+};
+use lance_rle_benchmark::format::FormatConfig;
+use lance_rle_benchmark::inspect::column_sizes;
+use lance_rle_benchmark::projection::leaf_columns;
+use lance_rle_benchmark::verify::{verify_batches, verify_roundtrip};
+
+const NUM_ROWS: usize = 1_000;
+
+async fn check_all_configs(generate_fn: fn(usize) -> RecordBatch) {
+    let batch = generate_fn(NUM_ROWS);
+    for config in FormatConfig::all() {
+        if let Err(error) = verify_roundtrip(config, &batch).await {
+            panic!("{} round-trip failed: {}", config.name(), error);
+        }
+    }
+}
+
+#[tokio::test]
+async fn nested_roundtrip() {
+    check_all_configs(generate_nested_record_batch).await;
+}
+
+#[tokio::test]
+async fn flat_roundtrip() {
+    check_all_configs(generate_flat_record_batch).await;
+}
+
+#[tokio::test]
+async fn nullable_roundtrip() {
+    check_all_configs(generate_nullable_record_batch).await;
+}
+
+#[tokio::test]
+async fn single_row_roundtrip() {
+    check_all_configs(|_| generate_nested_record_batch(1)).await;
+}
+
+#[test]
+fn detects_modified_value() {
+    let batch = generate_nullable_record_batch(16);
+    let other = generate_nullable_record_batch(17).slice(1, 16);
+
+    let mismatch = verify_batches(&batch, &[other]).unwrap_err();
+    assert_eq!(mismatch.column, "uuid");
+    assert_eq!(mismatch.row, Some(0));
+}
+
+#[test]
+fn detects_missing_rows() {
+    let batch = generate_nested_record_batch(16);
+    let mismatch = verify_batches(&batch, &[batch.slice(0, 15)]).unwrap_err();
+    assert_eq!(mismatch.row, None);
+}
+
+#[test]
+fn nullable_schema_has_struct_nulls() {
+    let batch = generate_nullable_record_batch(NUM_ROWS);
+    let features = batch.column_by_name("features").unwrap().as_struct();
+    assert!(features.null_count() > 0);
+    // Children are null wherever the struct is
+    for row in (0..features.len()).filter(|&row| features.is_null(row)) {
+        assert!(features.columns().iter().all(|child| child.is_null(row)));
+    }
+}
+
+#[tokio::test]
+async fn columns_are_named_after_their_fields() {
+    let batch = generate_nested_record_batch(NUM_ROWS);
+    let leaves = leaf_columns(&batch.schema());
+    for config in FormatConfig::all() {
+        let bytes = config.write(batch.clone()).await.unwrap();
+        let sizes = column_sizes(config, &bytes).await.unwrap();
+        let names: Vec<&str> = sizes.columns.iter().map(|c| c.column.as_str()).collect();
+        for leaf in &leaves {
+            assert!(
+                names.contains(&leaf.as_str()),
+                "{}: no column {}",
+                config.name(),
+                leaf
+            );
+        }
+        let features = sizes.struct_totals();
+        assert_eq!(features.len(), 1, "{}", config.name());
+        assert_eq!(features[0].0, "features");
+    }
+}